@@ -1,21 +1,62 @@
-use smallvec::{smallvec, SmallVec};
-
-use super::CPUPlayer;
+use super::{AiWeights, CPUPlayer, Difficulty, StrategyState};
 use crate::board::Board;
 use crate::log::TilePlacementEvent;
-use crate::path::{eval_placement, Offset, TilePath, TilePathType, TILE_PATHS};
+use crate::moves::{map_legal_turns, turn_score_under};
+use crate::path::eval_placement;
 use crate::player::TileArray;
+use crate::rules::RuleSet;
 use crate::score::TurnScore;
-use crate::tile::{Coordinates, Tile, ROTATIONS};
+use crate::tile::{Coordinates, GameRng, Tile};
+
+use rand::Rng;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Brute {
     player_count: usize,
+    /// Chooses randomly among this many of the best turns
+    top_n: usize,
+    /// Most tiles played in a single turn
+    max_tiles: usize,
+    weights: AiWeights,
+    rng: GameRng,
+    /// `None` when configured with custom weights
+    difficulty: Option<Difficulty>,
 }
 
 impl Brute {
+    /// The strongest configuration, `Difficulty::Expert`
     pub fn new(player_count: usize) -> Self {
-        Self { player_count }
+        Self::with_difficulty(player_count, Difficulty::Expert, 0)
+    }
+
+    /// `seed` determines the choices of difficulties that don't always play the best turn
+    pub fn with_difficulty(player_count: usize, difficulty: Difficulty, seed: u64) -> Self {
+        let config = difficulty.brute_config();
+        Self {
+            player_count,
+            top_n: config.top_n,
+            max_tiles: config.max_tiles,
+            weights: config.weights,
+            rng: GameRng::new(seed),
+            difficulty: Some(difficulty),
+        }
+    }
+
+    /// Rebuilds a strategy saved with `CPUPlayer::state`
+    pub(crate) fn from_state(player_count: usize, state: StrategyState) -> Self {
+        Self {
+            rng: state.rng,
+            ..Self::with_difficulty(player_count, state.difficulty, 0)
+        }
+    }
+
+    /// Always plays the turn that ranks highest under `weights`
+    pub fn with_weights(player_count: usize, weights: AiWeights) -> Self {
+        Self {
+            weights,
+            difficulty: None,
+            ..Self::new(player_count)
+        }
     }
 }
 
@@ -24,27 +65,58 @@ impl CPUPlayer for Brute {
         &mut self,
         tiles: &TileArray,
         board: &Board,
+        rules: &RuleSet,
         score: i16,
         other_scores: Vec<i16>,
     ) -> Vec<Vec<TilePlacementEvent>> {
-        let last_placement = board.last_placement();
-        let mut all_moves = self.ranked_moves(
-            board,
-            score,
-            &other_scores,
-            last_placement.0,
-            last_placement.1,
-            TurnScore::default(),
-            tiles,
-            &Vec::new(),
-        );
-        // Compare `y` against `x` for descending order
-        all_moves.sort_by(|x, y| y.score.cmp(&x.score));
+        let mut all_moves: Vec<PotentialSetOfMoves> =
+            map_legal_turns(board, tiles, rules.river_check, |placements| {
+                if placements.len() > self.max_tiles {
+                    return None;
+                }
+                let turn_score = turn_score_under(board, tiles, &placements, rules);
+                let (last_coordinates, last_offset) = placements
+                    .iter()
+                    .try_fold(board.last_placement(), eval_placement)
+                    .expect("legal turns align with the river");
+                Some(PotentialSetOfMoves {
+                    score: turn_score
+                        + self.next_tile_adjustment(board, last_coordinates + last_offset)
+                        + Self::end_game_adjustment(
+                            &self.weights,
+                            score,
+                            &other_scores,
+                            board.is_end_game_cell(last_coordinates),
+                            turn_score,
+                        )
+                        + self.rack_adjustment(tiles, &placements)
+                        + self.progress_adjustment(board, last_coordinates),
+                    placements,
+                })
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+        // Descending order
+        all_moves.sort_by_key(|potential_moves| std::cmp::Reverse(potential_moves.score));
+        let top_n = self.top_n.min(all_moves.len());
+        if top_n > 1 {
+            // Move the chosen turn to the front, keeping the rest as fallbacks
+            let choice = self.rng.gen_range(0, top_n);
+            all_moves[..=choice].rotate_right(1);
+        }
         all_moves
             .into_iter()
             .map(|potential_moves| potential_moves.placements)
             .collect()
     }
+
+    fn state(&self) -> Option<StrategyState> {
+        self.difficulty.map(|difficulty| StrategyState {
+            difficulty,
+            rng: self.rng.clone(),
+        })
+    }
 }
 
 struct PotentialSetOfMoves {
@@ -54,172 +126,45 @@ struct PotentialSetOfMoves {
     placements: Vec<TilePlacementEvent>,
 }
 
-fn tile_paths_from_tile(t: Tile) -> SmallVec<[TilePath; 8]> {
-    match t {
-        Tile::Straight => smallvec![TilePath::Straight],
-        Tile::Diagonal => smallvec![TilePath::Diagonal],
-        Tile::Center90 => smallvec![TilePath::Center90],
-        Tile::Corner90 => smallvec![TilePath::Corner90],
-        Tile::Left45 => smallvec![TilePath::Left45],
-        Tile::Right45 => smallvec![TilePath::Right45],
-        Tile::Left135 => smallvec![TilePath::Left135],
-        Tile::Right135 => smallvec![TilePath::Right135],
-        Tile::Universal => SmallVec::from(TILE_PATHS),
-    }
-}
-
 impl Brute {
-    #[allow(clippy::too_many_arguments)]
-    fn ranked_moves(
-        &self,
-        board: &Board,
-        score: i16,
-        other_scores: &[i16],
-        last_coordinates: Coordinates,
-        last_offset: Offset,
-        turn_score: TurnScore,
-        tiles: &TileArray,
-        placements: &[TilePlacementEvent],
-    ) -> Vec<PotentialSetOfMoves> {
-        let next_coordinates = last_coordinates + last_offset;
-        let mut potential_placements = Vec::new();
-        for (idx, tile) in tiles.iter().enumerate() {
-            for tile_path in tile_paths_from_tile(*tile) {
-                let tile_path_type = if *tile == Tile::Universal {
-                    TilePathType::Universal(tile_path)
-                } else {
-                    TilePathType::Normal(tile_path)
-                };
-                // Straight and diagonal only have two effective rotations because
-                // they're symettrical
-                let rotations =
-                    if tile_path == TilePath::Straight || tile_path == TilePath::Diagonal {
-                        &ROTATIONS[..2]
-                    } else {
-                        &ROTATIONS[..]
-                    };
-                for rotation in rotations {
-                    let placement = TilePlacementEvent {
-                        coordinates: next_coordinates,
-                        rotation: *rotation,
-                        tile_path_type,
-                    };
-                    // Check if valid placement and valid cell
-                    let (next_coordinates, next_offset, cell) = {
-                        let placement_res =
-                            eval_placement((last_coordinates, last_offset), &placement)
-                                .ok()
-                                .and_then(|p| board.cell(p.0).map(|cell| (p.0, p.1, cell)));
-                        match placement_res {
-                            Some(p) => p,
-                            None => {
-                                continue;
-                            }
-                        }
-                    };
-                    // Check if placement sends the river path off the board
-                    if !board.in_bounds(next_coordinates + next_offset)
-                                // If this is an end game cell, the next coordinates don't need
-                                // to be in bounds
-                                && !board.is_end_game_cell(next_coordinates)
-                    {
-                        continue;
-                    }
-                    // Can't replay in same place
-                    let has_replayed_in_cell = placements.iter().any(|placement| {
-                        placement.coordinates == next_coordinates
-                            || placement.coordinates == next_coordinates + next_offset
-                    }) || !cell.is_empty()
-                        || board.has_tile(next_coordinates + next_offset);
-                    if has_replayed_in_cell {
-                        continue;
-                    }
-                    if board.no_crossover(next_coordinates, next_offset).is_err() {
-                        continue;
-                    }
-                    // clone
-                    let mut new_placements = placements.to_owned();
-                    new_placements.push(placement);
-                    let new_score = turn_score
-                        + TurnScore::from(tile.score())
-                        + cell.score()
-                        + if tiles.len() == 1 {
-                            // TODO: fix when fewer than 5 tiles in rack
-                            // Bonus for using all tiles
-                            TurnScore::from(20)
-                        } else {
-                            TurnScore::default()
-                        };
-                    let end_game_adj = match Brute::end_game_adjustment(
-                        score,
-                        other_scores,
-                        board,
-                        new_score,
-                        &new_placements,
-                        (next_coordinates, next_offset),
-                    ) {
-                        Ok(adj) => adj,
-                        Err(()) => {
-                            continue;
-                        }
-                    };
-                    let set_of_moves = PotentialSetOfMoves {
-                        placements: new_placements.clone(),
-                        score: new_score
-                            + self.next_tile_adjustment(board, next_coordinates + next_offset)
-                            + end_game_adj,
-                    };
-                    potential_placements.push(set_of_moves);
-                    if tiles.len() > 1
-                                // Don't try to play more tiles if this set of moves
-                                // will already end the game
-                                && !board.is_end_game_cell(next_coordinates)
-                    {
-                        // Recurse
-                        let mut rem_tiles = tiles.clone();
-                        rem_tiles.remove(idx);
-                        potential_placements.extend(self.ranked_moves(
-                            board,
-                            score,
-                            other_scores,
-                            next_coordinates,
-                            next_offset,
-                            new_score,
-                            &rem_tiles,
-                            &new_placements,
-                        ));
-                    }
-                }
-            }
-        }
-        potential_placements
-    }
-
     fn next_tile_adjustment(&self, board: &Board, next_coordinates: Coordinates) -> TurnScore {
-        // this should be a function of the number of players. In a two-player game, the
-        // game is zero-sum
         match board.cell(next_coordinates) {
-            // when player count is 2, it's a zero-sum game, so forced penalties for the opponent
-            // are as valuable as bonuses for the cpu player
-            Some(cell) => -cell.score() * 2 / self.player_count as i16,
+            Some(cell) => -cell.score() * self.weights.next_tile / self.player_count as i16,
             _ => TurnScore::default(),
         }
     }
 
-    fn end_game_adjustment(
+    /// Universal tiles kept for later turns and playing every tile
+    fn rack_adjustment(&self, tiles: &[Tile], placements: &[TilePlacementEvent]) -> TurnScore {
+        let universal_count = |count: usize, tile: Tile| count + (tile == Tile::Universal) as usize;
+        let played_universal_count = placements
+            .iter()
+            .map(|placement| Tile::from(placement.tile_path_type))
+            .fold(0, universal_count);
+        let kept_universal_count =
+            tiles.iter().copied().fold(0, universal_count) - played_universal_count;
+        let all_tiles = if placements.len() == tiles.len() {
+            self.weights.all_tiles
+        } else {
+            0
+        };
+        TurnScore::from(self.weights.universal_tile * kept_universal_count as i16 + all_tiles)
+    }
+
+    /// How much closer the turn brings the river to the end-of-game column
+    fn progress_adjustment(&self, board: &Board, last_coordinates: Coordinates) -> TurnScore {
+        let Coordinates(_, start_column) = board.last_placement().0;
+        let Coordinates(_, end_column) = last_coordinates;
+        TurnScore::from(self.weights.column_progress * (end_column - start_column) as i16)
+    }
+
+    pub(super) fn end_game_adjustment(
+        weights: &AiWeights,
         score: i16,
         other_player_scores: &[i16],
-        board: &Board,
+        ends_game: bool,
         turn_score: TurnScore,
-        tile_placements: &[TilePlacementEvent],
-        last_placement: (Coordinates, Offset),
-    ) -> Result<TurnScore, ()> {
-        let end_of_game_cell_count = tile_placements
-            .iter()
-            .filter(|p| board.is_end_game_cell(p.coordinates))
-            .count();
-        let ends_game = Board::validate_end_of_game_cells(end_of_game_cell_count, last_placement)
-            .map_err(|_| ())?;
+    ) -> TurnScore {
         let total_score = score + turn_score.score();
         let rank = other_player_scores
             .iter()
@@ -228,10 +173,10 @@ impl Brute {
             + 1;
         match (ends_game, rank) {
             // Highly incentivize ending the game when winning
-            (true, 1) => Ok(TurnScore::from(1000)),
+            (true, 1) => TurnScore::from(weights.end_game_winning),
             // Want to penalize ending the game without winning
-            (true, _) => Ok(TurnScore::from(-100)),
-            (false, _) => Ok(TurnScore::default()),
+            (true, _) => TurnScore::from(weights.end_game_losing),
+            (false, _) => TurnScore::default(),
         }
     }
 }
@@ -240,7 +185,9 @@ impl Brute {
 mod test {
     use super::*;
     use crate::board::TilePlacement;
-    use crate::tile::Rotation;
+    use crate::path::{Offset, TilePath, TilePathType};
+    use crate::rules::RiverCheck;
+    use crate::tile::{Rotation, Tile};
 
     use smallvec::smallvec;
 
@@ -256,7 +203,7 @@ mod test {
             Tile::Straight,
             Tile::Straight,
         ];
-        let all_moves = target.take_turn(&tiles, &board, 0, vec![0]);
+        let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
         let moves = all_moves.first().unwrap();
         assert_eq!(moves.len(), 5);
         assert!(matches!(
@@ -321,7 +268,7 @@ mod test {
             Tile::Diagonal,
         ];
 
-        let all_moves = target.take_turn(&tiles, &board, 30, vec![50]);
+        let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 30, vec![50]);
         let moves = all_moves.first().unwrap();
         assert_eq!(moves.len(), 2);
     }
@@ -338,7 +285,7 @@ mod test {
             Tile::Straight,
         ];
 
-        let moves = target.take_turn(&tiles, &board, 0, vec![146]);
+        let moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![146]);
         assert!(moves.is_empty());
     }
 
@@ -355,7 +302,7 @@ mod test {
         ];
 
         // Optimal moves should place player in lead
-        let all_moves = target.take_turn(&tiles, &board, 400, vec![900, 885]);
+        let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 400, vec![900, 885]);
         let moves = all_moves.first().unwrap();
         assert_eq!(moves[0].coordinates, Coordinates(10, 20));
         assert_eq!(
@@ -368,4 +315,158 @@ mod test {
             TilePathType::Normal(TilePath::Straight)
         );
     }
+
+    #[test]
+    fn easy_plays_short_turns() {
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        for seed in 0..10 {
+            let mut target = Brute::with_difficulty(2, Difficulty::Easy, seed);
+            let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+            assert!(!all_moves.is_empty());
+            assert!(all_moves.iter().all(|moves| moves.len() <= 2));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_choice() {
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Universal,
+        ];
+        let choices: Vec<_> = (0..2)
+            .map(|_| {
+                let mut target = Brute::with_difficulty(2, Difficulty::Medium, 7);
+                (0..5)
+                    .map(|_| {
+                        target
+                            .take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0])
+                            .remove(0)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(choices[0], choices[1]);
+    }
+
+    #[test]
+    fn expert_plays_best_turn() {
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        let mut target = Brute::new(2);
+        let mut expert = Brute::with_difficulty(2, Difficulty::Expert, 99);
+        assert_eq!(
+            target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]),
+            expert.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0])
+        );
+    }
+
+    #[test]
+    fn keeps_universal_tiles() {
+        let board = Board::new();
+        let tiles = smallvec![Tile::Universal, Tile::Straight];
+        let mut target = Brute::new(2);
+        let moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert!(moves[0]
+            .iter()
+            .any(|m| matches!(m.tile_path_type, TilePathType::Universal(_))));
+
+        let mut target = Brute::with_weights(
+            2,
+            AiWeights {
+                universal_tile: 100,
+                ..AiWeights::default()
+            },
+        );
+        let moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert!(moves[0]
+            .iter()
+            .all(|m| m.tile_path_type == TilePathType::Normal(TilePath::Straight)));
+    }
+
+    #[test]
+    fn progress_toward_end_column() {
+        let board = Board::with_last_placement(Coordinates(10, 14), Offset(0, 1));
+        let target = Brute::with_weights(
+            2,
+            AiWeights {
+                column_progress: 5,
+                ..AiWeights::default()
+            },
+        );
+        assert_eq!(
+            target.progress_adjustment(&board, Coordinates(11, 17)),
+            TurnScore::from(15)
+        );
+        assert_eq!(
+            target.progress_adjustment(&board, Coordinates(9, 13)),
+            TurnScore::from(-5)
+        );
+        assert_eq!(
+            Brute::new(2).progress_adjustment(&board, Coordinates(11, 17)),
+            TurnScore::default()
+        );
+    }
+
+    #[test]
+    fn follows_river_check() {
+        // Continuing up column 2 leads into a pocket walled off by row 1 and column 5
+        let mut board = Board::with_last_placement(Coordinates(4, 2), Offset(-1, 0));
+        let straight =
+            |rotation| TilePlacement::new(TilePathType::Normal(TilePath::Straight), rotation);
+        for row in 0..crate::board::BOARD_DIM as i8 {
+            board
+                .place_tile(Coordinates(row, 5), straight(Rotation::Clockwise90))
+                .unwrap();
+        }
+        for column in 0..5 {
+            board
+                .place_tile(Coordinates(1, column), straight(Rotation::None))
+                .unwrap();
+        }
+        let tiles = smallvec![Tile::Straight];
+        let mut target = Brute::new(2);
+        assert!(target
+            .take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0])
+            .is_empty());
+        let rules = RuleSet {
+            river_check: RiverCheck::Escape,
+            ..RuleSet::default()
+        };
+        assert_eq!(
+            target.take_turn(&tiles, &board, &rules, 0, vec![0]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn scores_under_rules() {
+        let board = Board::new();
+        let tiles = smallvec![Tile::Straight, Tile::Straight];
+        let mut target = Brute::new(2);
+        let moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert_eq!(moves[0].len(), 2);
+        let rules = RuleSet {
+            all_tiles_bonus: -100,
+            ..RuleSet::default()
+        };
+        let moves = target.take_turn(&tiles, &board, &rules, 0, vec![0]);
+        assert_eq!(moves[0].len(), 1);
+    }
 }
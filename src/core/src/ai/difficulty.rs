@@ -0,0 +1,83 @@
+use std::fmt::Display;
+
+use super::AiWeights;
+
+use serde::{Deserialize, Serialize};
+
+/// How strong a CPU player is. Each level is a configuration of `Brute`
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Plays short turns and often misses the best one
+    Easy,
+    /// Sometimes misses the best turn
+    #[default]
+    Medium,
+    /// Always plays the highest-scoring turn
+    Hard,
+    /// Also considers the cell it leaves for the next player
+    Expert,
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Medium,
+    Difficulty::Hard,
+    Difficulty::Expert,
+];
+
+/// Settings of `Brute` for a `Difficulty`
+pub(crate) struct BruteConfig {
+    /// Chooses randomly among this many of the best turns
+    pub top_n: usize,
+    /// Most tiles played in a single turn
+    pub max_tiles: usize,
+    pub weights: AiWeights,
+}
+
+impl Difficulty {
+    pub(crate) fn brute_config(self) -> BruteConfig {
+        match self {
+            Difficulty::Easy => BruteConfig {
+                top_n: 5,
+                max_tiles: 2,
+                weights: AiWeights {
+                    next_tile: 0,
+                    ..AiWeights::default()
+                },
+            },
+            Difficulty::Medium => BruteConfig {
+                top_n: 3,
+                max_tiles: 3,
+                weights: AiWeights {
+                    next_tile: 0,
+                    ..AiWeights::default()
+                },
+            },
+            Difficulty::Hard => BruteConfig {
+                top_n: 1,
+                max_tiles: usize::MAX,
+                weights: AiWeights {
+                    next_tile: 0,
+                    ..AiWeights::default()
+                },
+            },
+            Difficulty::Expert => BruteConfig {
+                top_n: 1,
+                max_tiles: usize::MAX,
+                weights: AiWeights::default(),
+            },
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        };
+        write!(f, "{}", name)
+    }
+}
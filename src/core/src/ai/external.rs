@@ -0,0 +1,187 @@
+use super::CPUPlayer;
+use crate::board::Board;
+use crate::console;
+use crate::log::TilePlacementEvent;
+use crate::player::TileArray;
+use crate::protocol::{Position, PROTOCOL_VERSION};
+use crate::record::TurnRecord;
+use crate::rules::RuleSet;
+
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a bot has to reply before it's treated as having failed, unless spawned with another
+/// timeout
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a bot has to exit after `quit` before it's killed
+const QUIT_GRACE: Duration = Duration::from_millis(100);
+
+/// CPU player that runs as a separate program and speaks the text protocol in `protocol` over
+/// its stdin and stdout. Clones share the same process. If the program fails, takes too long to
+/// reply, or replies with something other than a turn, the player can't play
+/// that turn
+#[derive(Clone, Debug)]
+pub struct ExternalBot {
+    /// The name the program gave in its handshake
+    name: String,
+    process: Rc<RefCell<BotProcess>>,
+}
+
+impl ExternalBot {
+    /// Starts `program` and waits for it to be ready
+    pub fn spawn(program: &str, args: &[&str]) -> Result<Self, String> {
+        Self::spawn_with_timeout(program, args, REPLY_TIMEOUT)
+    }
+
+    /// Like `spawn`, giving the program `timeout` to reply to each request
+    pub fn spawn_with_timeout(
+        program: &str,
+        args: &[&str],
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let mut stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        let (sender, replies) = mpsc::channel();
+        // Reads on its own thread so waiting for a reply can time out. Stops when the bot closes
+        // its stdout, or when the `BotProcess` is dropped
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            let reply = match stdout.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => Ok(line),
+                Err(e) => Err(e),
+            };
+            let failed = reply.is_err();
+            if sender.send(reply).is_err() || failed {
+                break;
+            }
+        });
+        let mut process = BotProcess {
+            child,
+            stdin,
+            replies,
+            timeout,
+            last_request: 0,
+        };
+        process.send(&format!("nile {}\n", PROTOCOL_VERSION))?;
+        let reply = process.receive(Instant::now() + timeout)?;
+        let name = match reply.strip_prefix("ready") {
+            Some(name) => name.trim(),
+            None => return Err(format!("{} didn't start: {}", program, reply)),
+        };
+        Ok(Self {
+            name: if name.is_empty() { program } else { name }.to_owned(),
+            process: Rc::new(RefCell::new(process)),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl CPUPlayer for ExternalBot {
+    fn take_turn(
+        &mut self,
+        tiles: &TileArray,
+        board: &Board,
+        rules: &RuleSet,
+        score: i16,
+        other_scores: Vec<i16>,
+    ) -> Vec<Vec<TilePlacementEvent>> {
+        let position = Position::new(board, rules, tiles, score, &other_scores);
+        match self.process.borrow_mut().request_turn(&position) {
+            Ok(TurnRecord::Placements(placements)) => vec![placements],
+            Ok(TurnRecord::CantPlay) => Vec::new(),
+            Err(e) => {
+                console::warn(&format!("{}: {}", self.name, e));
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines from the bot's stdout
+    replies: Receiver<io::Result<String>>,
+    timeout: Duration,
+    /// Id of the last turn requested
+    last_request: u64,
+}
+
+impl BotProcess {
+    fn send(&mut self, message: &str) -> Result<(), String> {
+        self.stdin
+            .write_all(message.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Failed to write to bot: {}", e))
+    }
+
+    /// Waits for the next line until `deadline`
+    fn receive(&mut self, deadline: Instant) -> Result<String, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.replies.recv_timeout(timeout) {
+            Ok(Ok(line)) => Ok(line.trim().to_owned()),
+            Ok(Err(e)) => Err(format!("Failed to read from bot: {}", e)),
+            Err(RecvTimeoutError::Timeout) => Err(format!(
+                "Bot didn't reply within {}ms",
+                self.timeout.as_millis()
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err("Bot exited".to_owned()),
+        }
+    }
+
+    fn request_turn(&mut self, position: &Position) -> Result<TurnRecord, String> {
+        self.last_request += 1;
+        let id = self.last_request.to_string();
+        self.send(&format!("{}go {}\n", position, id))?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let reply = self.receive(deadline)?;
+            let mut words = reply.splitn(3, ' ');
+            let (kind, reply_id, rest) = (words.next(), words.next(), words.next());
+            match (kind, reply_id) {
+                (Some("turn"), Some(reply_id)) if reply_id == id => {
+                    let turn = rest.unwrap_or_default();
+                    return turn
+                        .parse()
+                        .map_err(|e| format!("Invalid turn `{}`: {}", turn, e));
+                }
+                (Some("error"), Some(reply_id)) if reply_id == id => {
+                    return Err(rest.unwrap_or_default().to_owned())
+                }
+                // Late replies to earlier requests that timed out
+                (Some("turn" | "error"), Some(reply_id))
+                    if reply_id.parse::<u64>().is_ok_and(|n| n < self.last_request) => {}
+                _ => return Err(format!("Expected a turn, got `{}`", reply)),
+            }
+        }
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        // Give the bot a moment to exit on its own before killing it
+        let _ = self.send("quit\n");
+        let deadline = Instant::now() + QUIT_GRACE;
+        while matches!(self.child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
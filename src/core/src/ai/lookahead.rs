@@ -0,0 +1,309 @@
+use super::{searched_first, unseen_tiles, AiWeights, Brute, CPUPlayer};
+use crate::board::Board;
+use crate::log::TilePlacementEvent;
+use crate::moves::{legal_turns_under, play_turn, turn_score_under};
+use crate::path::eval_placement;
+use crate::player::TileArray;
+use crate::rules::RuleSet;
+use crate::tile::{GameRng, Tile};
+
+use rand::seq::SliceRandom;
+
+/// How many of the highest-scoring turns have the next player's reply searched
+const BREADTH: usize = 10;
+/// Racks sampled for the next player for each searched turn
+const SAMPLES: usize = 6;
+
+/// Limits how much searching `Lookahead` does each turn
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchBudget {
+    /// Turns evaluated, both its own and the next player's possible replies. At least one of
+    /// its own turns is always evaluated
+    Nodes(usize),
+    /// Wall-clock time
+    Millis(u32),
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        Self::Nodes(100_000)
+    }
+}
+
+/// Expectimax CPU player. Along with the score of its own turn, it estimates the score of the
+/// next player's best reply, averaged over racks drawn from the tiles it hasn't seen. This
+/// avoids leaving the river pointing at bonuses for the next player and favors leaving it
+/// pointing at penalties
+#[derive(Clone, Debug)]
+pub struct Lookahead {
+    player_count: usize,
+    budget: SearchBudget,
+    /// Samples the next player's racks
+    rng: GameRng,
+}
+
+impl Lookahead {
+    pub fn new(player_count: usize, budget: SearchBudget, seed: u64) -> Self {
+        Self {
+            player_count,
+            budget,
+            rng: GameRng::new(seed),
+        }
+    }
+
+    /// Expected score of the next player's best reply after `placements`. `None` if the budget
+    /// ran out first
+    fn expected_reply(
+        &mut self,
+        board: &Board,
+        rules: &RuleSet,
+        placements: &[TilePlacementEvent],
+        unseen: &[Tile],
+        budget: &mut Budget,
+    ) -> Option<f32> {
+        let mut board = board.clone();
+        play_turn(&mut board, placements, rules.river_check).expect("legal turns can be played");
+        let mut total = 0.0;
+        for _ in 0..SAMPLES {
+            let rack: Vec<Tile> = unseen
+                .choose_multiple(&mut self.rng, rules.rack_size)
+                .cloned()
+                .collect();
+            total += Self::best_reply(&board, rules, &rack, budget)? as f32;
+        }
+        Some(total / SAMPLES as f32)
+    }
+
+    fn best_reply(
+        board: &Board,
+        rules: &RuleSet,
+        rack: &[Tile],
+        budget: &mut Budget,
+    ) -> Option<i16> {
+        let mut best = None;
+        for placements in legal_turns_under(board, rack, rules.river_check) {
+            if !budget.spend() {
+                return None;
+            }
+            let score = turn_score_under(board, rack, &placements, rules).score();
+            best = best.max(Some(score));
+        }
+        // Can't play, so takes the penalty
+        Some(best.unwrap_or_else(|| -rules.cant_play_penalty(rack)))
+    }
+}
+
+struct Candidate {
+    placements: Vec<TilePlacementEvent>,
+    /// Score of the turn itself, adjusted for ending the game
+    score: f32,
+    ends_game: bool,
+    /// `score` less the next player's expected reply. `None` if not searched
+    value: Option<f32>,
+}
+
+impl CPUPlayer for Lookahead {
+    fn take_turn(
+        &mut self,
+        tiles: &TileArray,
+        board: &Board,
+        rules: &RuleSet,
+        score: i16,
+        other_scores: Vec<i16>,
+    ) -> Vec<Vec<TilePlacementEvent>> {
+        let mut budget = Budget::new(self.budget);
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for placements in legal_turns_under(board, tiles, rules.river_check) {
+            // Scoring the candidates counts against the budget like searching replies does. The
+            // first is always scored so there's a turn to play
+            if !budget.spend() && !candidates.is_empty() {
+                break;
+            }
+            let turn_score = turn_score_under(board, tiles, &placements, rules);
+            let (last_coordinates, _) = placements
+                .iter()
+                .try_fold(board.last_placement(), eval_placement)
+                .expect("legal turns align with the river");
+            let ends_game = board.is_end_game_cell(last_coordinates);
+            let adjusted = turn_score
+                + Brute::end_game_adjustment(
+                    &AiWeights::default(),
+                    score,
+                    &other_scores,
+                    ends_game,
+                    turn_score,
+                );
+            candidates.push(Candidate {
+                placements,
+                score: adjusted.score() as f32,
+                ends_game,
+                value: None,
+            });
+        }
+        candidates.sort_by(|x, y| y.score.total_cmp(&x.score));
+
+        let unseen = unseen_tiles(board, tiles);
+        // Forcing a penalty on the next player is only as valuable as a bonus for this player
+        // in a two-player game
+        let reply_weight = 2.0 / self.player_count as f32;
+        // Replies are searched one candidate at a time because the budget they share decides
+        // which candidates are searched, and the racks are drawn from one generator
+        for candidate in candidates.iter_mut().take(BREADTH) {
+            if candidate.ends_game {
+                candidate.value = Some(candidate.score);
+                continue;
+            }
+            match self.expected_reply(board, rules, &candidate.placements, &unseen, &mut budget) {
+                Some(reply) => candidate.value = Some(candidate.score - reply * reply_weight),
+                None => break,
+            }
+        }
+        // Searched turns first. Unsearched turns keep their order as fallbacks
+        candidates.sort_by(|x, y| searched_first(x.value, y.value));
+        candidates.into_iter().map(|c| c.placements).collect()
+    }
+}
+
+/// What's left of a `SearchBudget` during a turn
+struct Budget {
+    nodes_left: Option<usize>,
+    /// In milliseconds
+    deadline: Option<f64>,
+}
+
+impl Budget {
+    fn new(budget: SearchBudget) -> Self {
+        match budget {
+            SearchBudget::Nodes(nodes) => Self {
+                nodes_left: Some(nodes),
+                deadline: None,
+            },
+            SearchBudget::Millis(millis) => Self {
+                nodes_left: None,
+                deadline: Some(now_millis() + millis as f64),
+            },
+        }
+    }
+
+    /// Accounts for evaluating one more node. Returns whether there was budget left for it
+    fn spend(&mut self) -> bool {
+        if let Some(nodes_left) = self.nodes_left.as_mut() {
+            if *nodes_left == 0 {
+                return false;
+            }
+            *nodes_left -= 1;
+        }
+        self.deadline.is_none_or(|deadline| now_millis() < deadline)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn now_millis() -> f64;
+}
+
+// `std::time` isn't supported in the browser
+#[cfg(not(target_arch = "wasm32"))]
+fn now_millis() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{BoardLayout, CellBonus};
+    use crate::moves::legal_turns;
+    use crate::rules::RiverCheck;
+    use crate::tile::Coordinates;
+
+    use smallvec::smallvec;
+
+    #[test]
+    fn plays_legal_turns() {
+        let mut target = Lookahead::new(2, SearchBudget::Nodes(5_000), 1);
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert_eq!(all_moves.len(), legal_turns(&board, &tiles).count());
+        for moves in all_moves {
+            assert!(play_turn(&mut board.clone(), &moves, RiverCheck::default()).is_ok());
+        }
+    }
+
+    #[test]
+    fn exhausted_budget_plays_first_turn() {
+        let mut target = Lookahead::new(2, SearchBudget::Nodes(0), 1);
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        assert_eq!(
+            target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]),
+            [legal_turns(&board, &tiles).next().unwrap()]
+        );
+    }
+
+    #[test]
+    fn budget_limits_own_turns() {
+        let mut target = Lookahead::new(2, SearchBudget::Nodes(3), 1);
+        let board = Board::new();
+        let tiles = smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        let all_moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert_eq!(all_moves.len(), 3);
+    }
+
+    #[test]
+    fn avoids_setting_up_next_player() {
+        let layout = BoardLayout {
+            bonuses: vec![
+                CellBonus {
+                    coordinates: Coordinates(7, 2),
+                    bonus: 60,
+                },
+                CellBonus {
+                    coordinates: Coordinates(13, 2),
+                    bonus: -40,
+                },
+            ],
+            ..BoardLayout::default()
+        };
+        let board = Board::from_layout(&layout).unwrap();
+        // Both ways of playing every tile score the same, but one leaves the river pointing at
+        // the bonus and the other at the penalty
+        let tiles = smallvec![Tile::Left45, Tile::Right45, Tile::Diagonal];
+        let next_cell = |moves: &[TilePlacementEvent]| {
+            let mut board = board.clone();
+            play_turn(&mut board, moves, RiverCheck::default()).unwrap();
+            let (coordinates, offset) = board.last_placement();
+            coordinates + offset
+        };
+        let mut brute = Brute::with_difficulty(2, crate::ai::Difficulty::Hard, 1);
+        let brute_moves = brute.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert_eq!(next_cell(&brute_moves[0]), Coordinates(7, 2));
+
+        let mut target = Lookahead::new(2, SearchBudget::Nodes(50_000), 1);
+        let moves = target.take_turn(&tiles, &board, &RuleSet::default(), 0, vec![0]);
+        assert_eq!(moves[0].len(), 3);
+        assert_eq!(next_cell(&moves[0]), Coordinates(13, 2));
+    }
+}
@@ -1,18 +1,129 @@
 mod brute;
+mod difficulty;
+#[cfg(not(target_arch = "wasm32"))]
+mod external;
+mod lookahead;
+mod monte_carlo;
+mod weights;
 
 use crate::board::Board;
 use crate::log::TilePlacementEvent;
 use crate::player::TileArray;
+use crate::rules::RuleSet;
+use crate::tile::{GameRng, Tile, TileBox};
 
-/// An automated player to compete with human players and other `CPUPlayer`s
-pub trait CPUPlayer: std::fmt::Debug {
+use serde::{Deserialize, Serialize};
+
+/// An automated player to compete with human players and other `CPUPlayer`s. Each CPU player
+/// owns its own `CPUPlayer`, so implementations can keep state between turns. Turns are searched
+/// under the game's `rules`. `other_scores` are in turn order, starting with the next player
+pub trait CPUPlayer: std::fmt::Debug + CloneCPUPlayer {
     fn take_turn(
         &mut self,
         tiles: &TileArray,
         board: &Board,
+        rules: &RuleSet,
         score: i16,
         other_scores: Vec<i16>,
     ) -> Vec<Vec<TilePlacementEvent>>;
+
+    /// What to save so the strategy can be rebuilt exactly when the game is loaded. Strategies
+    /// without one are replaced by the default strategy
+    fn state(&self) -> Option<StrategyState> {
+        None
+    }
+}
+
+/// Saved form of a `Difficulty`'s strategy: the difficulty and how far its randomness has
+/// advanced
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StrategyState {
+    difficulty: Difficulty,
+    rng: GameRng,
+}
+
+/// Allows cloning a `Box<dyn CPUPlayer>`, which is necessary for cloning a game. Implemented
+/// for every `CPUPlayer` that implements `Clone`
+pub trait CloneCPUPlayer {
+    fn clone_box(&self) -> Box<dyn CPUPlayer>;
+}
+
+impl<T: CPUPlayer + Clone + 'static> CloneCPUPlayer for T {
+    fn clone_box(&self) -> Box<dyn CPUPlayer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CPUPlayer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
 pub use brute::Brute;
+pub use difficulty::{Difficulty, DIFFICULTIES};
+#[cfg(not(target_arch = "wasm32"))]
+pub use external::ExternalBot;
+pub use lookahead::{Lookahead, SearchBudget};
+pub use monte_carlo::MonteCarlo;
+pub use weights::AiWeights;
+
+/// Tiles that are either still in the box or in another player's rack
+pub(crate) fn unseen_tiles(board: &Board, rack: &[Tile]) -> Vec<Tile> {
+    let mut unseen = TileBox::all_tiles();
+    let seen = board
+        .tiles()
+        .map(|(_, tile_placement)| Tile::from(*tile_placement.tile_path_type()))
+        .chain(rack.iter().copied());
+    for tile in seen {
+        if let Some(idx) = unseen.iter().position(|t| *t == tile) {
+            unseen.swap_remove(idx);
+        }
+    }
+    unseen
+}
+
+/// Orders searched candidates, those with a value, before the rest and from the highest value.
+/// Sorting is stable, so unsearched candidates keep their order as fallbacks
+pub(crate) fn searched_first(x: Option<f32>, y: Option<f32>) -> std::cmp::Ordering {
+    match (x, y) {
+        (Some(x), Some(y)) => y.total_cmp(&x),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+/// Removes the tiles played in `placements` from `rack`
+pub(crate) fn remove_played(rack: &mut Vec<Tile>, placements: &[TilePlacementEvent]) {
+    for placement in placements {
+        let tile = Tile::from(placement.tile_path_type);
+        let idx = rack
+            .iter()
+            .position(|t| *t == tile)
+            .expect("tile from rack");
+        rack.remove(idx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::TilePlacement;
+    use crate::path::{TilePath, TilePathType};
+    use crate::tile::{Coordinates, Rotation};
+
+    #[test]
+    fn unseen_excludes_board_and_rack() {
+        let mut board = Board::new();
+        board
+            .place_tile(
+                Coordinates(10, 0),
+                TilePlacement::new(TilePathType::Universal(TilePath::Straight), Rotation::None),
+            )
+            .unwrap();
+        let unseen = unseen_tiles(&board, &[Tile::Universal, Tile::Diagonal]);
+        assert_eq!(unseen.len(), TileBox::all_tiles().len() - 3);
+        assert_eq!(unseen.iter().filter(|t| **t == Tile::Universal).count(), 2);
+    }
+}
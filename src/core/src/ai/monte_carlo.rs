@@ -0,0 +1,289 @@
+use super::{remove_played, searched_first, unseen_tiles, CPUPlayer};
+use crate::board::Board;
+use crate::log::TilePlacementEvent;
+use crate::moves::{legal_turns_under, map_legal_turns, play_turn, turn_score_under};
+use crate::parallel;
+use crate::player::TileArray;
+use crate::rules::RuleSet;
+use crate::tile::{GameRng, Tile};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How many of the highest-scoring turns are simulated
+const BREADTH: usize = 8;
+/// Legal turns the default policy considers each turn of a rollout
+const ROLLOUT_TURN_LIMIT: usize = 32;
+/// Stops rollouts of games that would otherwise take unusually long to finish
+const MAX_ROLLOUT_TURNS: usize = 200;
+
+/// CPU player that simulates complete games after each of its best-scoring turns and plays the
+/// one with the best average final score margin over the leading opponent.
+///
+/// Each rollout deals the other players' racks and the order of the tile box from the tiles it
+/// hasn't seen, then every player greedily plays the best of the first few legal turns. The
+/// other players are assumed to take turns in the order of `other_scores`. Given the same seed
+/// the same turns are chosen
+#[derive(Clone, Debug)]
+pub struct MonteCarlo {
+    player_count: usize,
+    /// Simulated games per candidate turn
+    rollouts: usize,
+    rng: GameRng,
+}
+
+impl MonteCarlo {
+    pub fn new(player_count: usize, rollouts: usize, seed: u64) -> Self {
+        Self {
+            player_count,
+            rollouts,
+            rng: GameRng::new(seed),
+        }
+    }
+
+    /// Average final score margin after playing `placements`. `scores` are the scores before the
+    /// turn, starting with the CPU player's
+    fn simulate(
+        &self,
+        board: &Board,
+        rules: &RuleSet,
+        rack: &[Tile],
+        placements: &[TilePlacementEvent],
+        scores: &[i32],
+        rng: &mut GameRng,
+    ) -> f32 {
+        let mut after = board.clone();
+        let has_ended = play_turn(&mut after, placements, rules.river_check)
+            .expect("legal turns can be played");
+        let mut scores = scores.to_vec();
+        scores[0] += i32::from(turn_score_under(board, rack, placements, rules).score());
+        if has_ended {
+            return margin(&scores) as f32;
+        }
+        let mut remaining = rack.to_vec();
+        remove_played(&mut remaining, placements);
+        let unseen = unseen_tiles(board, rack);
+
+        let mut total = 0;
+        for _ in 0..self.rollouts {
+            let mut tile_box = unseen.clone();
+            tile_box.shuffle(rng);
+            let mut racks = vec![remaining.clone()];
+            for _ in 1..self.player_count {
+                racks.push(Vec::new());
+            }
+            for rack in racks.iter_mut() {
+                refill(rack, &mut tile_box, rules.rack_size);
+            }
+            let rollout = Rollout {
+                board: after.clone(),
+                rules,
+                racks,
+                scores: scores.clone(),
+                tile_box,
+                // The next player
+                current: 1 % self.player_count,
+            };
+            total += rollout.play_out(rng);
+        }
+        total as f32 / self.rollouts.max(1) as f32
+    }
+}
+
+impl CPUPlayer for MonteCarlo {
+    fn take_turn(
+        &mut self,
+        tiles: &TileArray,
+        board: &Board,
+        rules: &RuleSet,
+        score: i16,
+        other_scores: Vec<i16>,
+    ) -> Vec<Vec<TilePlacementEvent>> {
+        let mut candidates: Vec<Candidate> =
+            map_legal_turns(board, tiles, rules.river_check, |placements| Candidate {
+                score: turn_score_under(board, tiles, &placements, rules).score(),
+                placements,
+                value: None,
+            });
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+        let scores: Vec<i32> = std::iter::once(score)
+            .chain(other_scores)
+            .map(i32::from)
+            .collect();
+        // Each candidate is simulated with its own generator so they can be simulated in any
+        // order, or at the same time
+        let simulated = BREADTH.min(candidates.len());
+        let seeds: Vec<u64> = (0..simulated).map(|_| self.rng.gen()).collect();
+        let values = parallel::map(
+            candidates[..simulated].iter().zip(seeds).collect(),
+            |(candidate, seed)| {
+                let mut rng = GameRng::new(seed);
+                let placements = &candidate.placements;
+                self.simulate(board, rules, tiles, placements, &scores, &mut rng)
+            },
+        );
+        for (candidate, value) in candidates.iter_mut().zip(values) {
+            candidate.value = Some(value);
+        }
+        // Simulated turns first. The rest keep their order as fallbacks
+        candidates.sort_by(|x, y| searched_first(x.value, y.value));
+        candidates.into_iter().map(|c| c.placements).collect()
+    }
+}
+
+struct Candidate {
+    placements: Vec<TilePlacementEvent>,
+    score: i16,
+    /// Average final score margin. `None` if not simulated
+    value: Option<f32>,
+}
+
+/// A simulated game from after a candidate turn to the end
+struct Rollout<'a> {
+    board: Board,
+    rules: &'a RuleSet,
+    /// Index 0 is the CPU player, followed by the other players in order
+    racks: Vec<Vec<Tile>>,
+    scores: Vec<i32>,
+    /// Draws from the end
+    tile_box: Vec<Tile>,
+    current: usize,
+}
+
+impl Rollout<'_> {
+    /// Plays until the game ends and returns the final score margin of the CPU player
+    fn play_out(mut self, rng: &mut GameRng) -> i32 {
+        let player_count = self.racks.len();
+        let mut cant_play_count = 0;
+        for _ in 0..MAX_ROLLOUT_TURNS {
+            let rack = &mut self.racks[self.current];
+            let best = legal_turns_under(&self.board, rack, self.rules.river_check)
+                .take(ROLLOUT_TURN_LIMIT)
+                .map(|placements| {
+                    let score =
+                        turn_score_under(&self.board, rack, &placements, self.rules).score();
+                    (placements, score)
+                })
+                // The first of equally-scoring turns
+                .fold(
+                    None,
+                    |best: Option<(Vec<TilePlacementEvent>, i16)>, turn| match best {
+                        Some(best) if best.1 >= turn.1 => Some(best),
+                        _ => Some(turn),
+                    },
+                );
+            match best {
+                Some((placements, score)) => {
+                    let has_ended = play_turn(&mut self.board, &placements, self.rules.river_check)
+                        .expect("legal turns can be played");
+                    self.scores[self.current] += i32::from(score);
+                    remove_played(rack, &placements);
+                    refill(rack, &mut self.tile_box, self.rules.rack_size);
+                    cant_play_count = 0;
+                    if has_ended {
+                        break;
+                    }
+                }
+                None => {
+                    self.scores[self.current] -= i32::from(self.rules.cant_play_penalty(rack));
+                    // Discarded tiles return to random places in the box
+                    for tile in rack.drain(..) {
+                        let idx = rng.gen_range(0, self.tile_box.len() + 1);
+                        self.tile_box.insert(idx, tile);
+                    }
+                    refill(rack, &mut self.tile_box, self.rules.rack_size);
+                    cant_play_count += 1;
+                    if cant_play_count == player_count {
+                        break;
+                    }
+                }
+            }
+            self.current = (self.current + 1) % player_count;
+            if self.racks[self.current].is_empty() {
+                break;
+            }
+        }
+        margin(&self.scores)
+    }
+}
+
+fn refill(rack: &mut Vec<Tile>, tile_box: &mut Vec<Tile>, rack_size: usize) {
+    while rack.len() < rack_size {
+        match tile_box.pop() {
+            Some(tile) => rack.push(tile),
+            None => break,
+        }
+    }
+}
+
+/// How far the CPU player, at index 0, is ahead of the best of the other players
+fn margin(scores: &[i32]) -> i32 {
+    let best_other = scores[1..].iter().copied().max().unwrap_or_default();
+    scores[0] - best_other
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::moves::{legal_turns, turn_score};
+    use crate::rules::RiverCheck;
+
+    use smallvec::smallvec;
+
+    fn tiles() -> TileArray {
+        smallvec![
+            Tile::Right45,
+            Tile::Left45,
+            Tile::Straight,
+            Tile::Diagonal,
+            Tile::Universal,
+        ]
+    }
+
+    #[test]
+    fn same_seed_same_turns() {
+        let board = Board::new();
+        let mut target1 = MonteCarlo::new(2, 2, 5);
+        let mut target2 = MonteCarlo::new(2, 2, 5);
+        assert_eq!(
+            target1.take_turn(&tiles(), &board, &RuleSet::default(), 0, vec![0]),
+            target2.take_turn(&tiles(), &board, &RuleSet::default(), 0, vec![0])
+        );
+    }
+
+    #[test]
+    fn plays_legal_turns() {
+        let board = Board::new();
+        let mut target = MonteCarlo::new(3, 1, 5);
+        let all_moves = target.take_turn(&tiles(), &board, &RuleSet::default(), 0, vec![0, 0]);
+        assert_eq!(all_moves.len(), legal_turns(&board, &tiles()).count());
+        for moves in all_moves {
+            assert!(play_turn(&mut board.clone(), &moves, RiverCheck::default()).is_ok());
+        }
+    }
+
+    #[test]
+    fn rollout_plays_to_end_of_game() {
+        let board = Board::new();
+        let rack = vec![Tile::Straight];
+        let turns: Vec<_> = legal_turns(&board, &rack).collect();
+        assert_eq!(turns.len(), 1);
+        let expected = 10 + i32::from(turn_score(&board, &rack, &turns[0]).score());
+        let rollout = Rollout {
+            board,
+            rules: &RuleSet::default(),
+            racks: vec![rack, Vec::new()],
+            scores: vec![10, 0],
+            tile_box: Vec::new(),
+            current: 0,
+        };
+        // The game ends once the next player has no tiles
+        assert_eq!(rollout.play_out(&mut GameRng::new(0)), expected);
+    }
+
+    #[test]
+    fn margin_against_leader() {
+        assert_eq!(margin(&[50, 20, 70]), -20);
+        assert_eq!(margin(&[50, 20]), 30);
+    }
+}
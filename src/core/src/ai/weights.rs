@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// Terms a CPU player adds to a turn's score when ranking turns. The defaults reproduce the
+/// original `Brute`; the newer terms are off
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiWeights {
+    /// Added for ending the game while in the lead
+    pub end_game_winning: i16,
+    /// Added for ending the game while not in the lead. Normally negative
+    pub end_game_losing: i16,
+    /// The cell the next player has to play on is worth `-cell score * next_tile / player count`.
+    /// With two players the game is zero-sum, so a penalty for the next player is as valuable
+    /// as a bonus of the same size
+    pub next_tile: i16,
+    /// Added for each universal tile left in the rack after the turn
+    pub universal_tile: i16,
+    /// Added for each column the turn moves the river toward the end-of-game column
+    pub column_progress: i16,
+    /// Added, beyond the game's bonus, for playing every tile in the rack
+    pub all_tiles: i16,
+}
+
+impl Default for AiWeights {
+    fn default() -> Self {
+        Self {
+            end_game_winning: 1000,
+            end_game_losing: -100,
+            next_tile: 2,
+            universal_tile: 0,
+            column_progress: 0,
+            all_tiles: 0,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::board::Board;
+use crate::log::TilePlacementEvent;
+use crate::moves::{legal_turns_under, turn_score_under};
+use crate::parallel;
+use crate::player::TileArray;
+use crate::record::{GameRecord, TurnRecord};
+use crate::replay::Replay;
+use crate::rules::RuleSet;
+use crate::tile::Tile;
+
+/// How a committed turn compares to the turns the player could have played instead
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurnAnalysis {
+    /// Index of the turn in `GameRecord::turns`
+    pub turn: usize,
+    /// Index of the player who took the turn
+    pub player: usize,
+    /// Net score of the turn as played. “Can’t play” scores the discard penalty
+    pub score: i16,
+    /// Highest scoring turn available, or “can’t play” if there were no legal turns
+    pub best: TurnRecord,
+    pub best_score: i16,
+    /// Net score of the lowest scoring turn available
+    pub worst_score: i16,
+}
+
+impl TurnAnalysis {
+    /// Points given up by not playing the best turn
+    pub fn missed(&self) -> i16 {
+        (self.best_score - self.score).max(0)
+    }
+
+    /// Where the turn falls between the worst (0.0) and best (1.0) turns available. 1.0 when
+    /// every available turn scored the same
+    pub fn accuracy(&self) -> f32 {
+        if self.best_score <= self.worst_score {
+            1.0
+        } else {
+            let range = (self.best_score - self.worst_score) as f32;
+            ((self.score - self.worst_score) as f32 / range).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Totals of a player's `TurnAnalysis`es
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerAnalysis {
+    pub turns: usize,
+    /// Points given up over the whole game
+    pub missed: i16,
+    /// Average `TurnAnalysis::accuracy` of the player's turns, from 0.0 to 1.0
+    pub accuracy: f32,
+}
+
+/// Every committed turn of a game compared to the best turn that was available, as found by the
+/// legal move search
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameAnalysis {
+    turns: Vec<TurnAnalysis>,
+    players: Vec<PlayerAnalysis>,
+}
+
+impl GameAnalysis {
+    /// Replays `record` to find each player's rack and board before every turn
+    pub fn new(record: &GameRecord) -> Result<Self, String> {
+        let mut replay = Replay::new(record.clone())?;
+        let positions: Vec<(usize, usize, Board, TileArray, &TurnRecord)> = record
+            .turns
+            .iter()
+            .enumerate()
+            .map(|(turn, turn_record)| {
+                replay.seek(turn);
+                let nile = replay.nile();
+                (
+                    turn,
+                    nile.current_turn(),
+                    nile.board().clone(),
+                    nile.current_player().tiles().clone(),
+                    turn_record,
+                )
+            })
+            .collect();
+        let rules = &record.rules;
+        let turns = parallel::map(positions, |(turn, player, board, rack, turn_record)| {
+            analyze_turn(turn, player, &board, &rack, turn_record, rules)
+        });
+        let mut players = vec![PlayerAnalysis::default(); record.players.len()];
+        for turn in turns.iter() {
+            let player = &mut players[turn.player];
+            player.turns += 1;
+            player.missed += turn.missed();
+            player.accuracy += turn.accuracy();
+        }
+        for player in players.iter_mut() {
+            player.accuracy = if player.turns == 0 {
+                1.0
+            } else {
+                player.accuracy / player.turns as f32
+            };
+        }
+        Ok(Self { turns, players })
+    }
+
+    /// Analysis of every committed turn in order
+    pub fn turns(&self) -> &[TurnAnalysis] {
+        &self.turns
+    }
+
+    /// Totals for each player, indexed like `GameRecord::players`
+    pub fn players(&self) -> &[PlayerAnalysis] {
+        &self.players
+    }
+
+    /// Up to `count` turns that missed the most points, biggest first. Turns that missed nothing
+    /// aren't blunders
+    pub fn blunders(&self, count: usize) -> Vec<&TurnAnalysis> {
+        let mut blunders: Vec<&TurnAnalysis> =
+            self.turns.iter().filter(|t| t.missed() > 0).collect();
+        // Stable sort keeps earlier turns first among equal misses
+        blunders.sort_by_key(|t| -t.missed());
+        blunders.truncate(count);
+        blunders
+    }
+}
+
+fn analyze_turn(
+    turn: usize,
+    player: usize,
+    board: &Board,
+    rack: &[Tile],
+    turn_record: &TurnRecord,
+    rules: &RuleSet,
+) -> TurnAnalysis {
+    let score_of = |placements: &[TilePlacementEvent]| {
+        turn_score_under(board, rack, placements, rules).score()
+    };
+    let cant_play_score = -rules.cant_play_penalty(rack);
+    let score = match turn_record {
+        TurnRecord::Placements(placements) => score_of(placements),
+        TurnRecord::CantPlay => cant_play_score,
+    };
+    let mut best: Option<(Vec<TilePlacementEvent>, i16)> = None;
+    let mut worst_score = None;
+    for placements in legal_turns_under(board, rack, rules.river_check) {
+        let placements_score = score_of(&placements);
+        worst_score = Some(worst_score.map_or(placements_score, |w: i16| w.min(placements_score)));
+        // Only replace on a higher score so the first of equally good turns is kept
+        if best.as_ref().is_none_or(|(_, s)| placements_score > *s) {
+            best = Some((placements, placements_score));
+        }
+    }
+    let (best, best_score) = match best {
+        Some((placements, best_score)) => (TurnRecord::Placements(placements), best_score),
+        None => (TurnRecord::CantPlay, cant_play_score),
+    };
+    TurnAnalysis {
+        turn,
+        player,
+        score,
+        best,
+        best_score,
+        worst_score: worst_score.unwrap_or(cant_play_score),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::BoardLayout;
+    use crate::nile::Nile;
+    use crate::player::PlayerSetup;
+    use crate::rules::CantPlayCheck;
+
+    /// Plays `turn_count` turns, choosing each from the legal turns with `pick`
+    fn game(turn_count: usize, pick: fn(Vec<Vec<TilePlacementEvent>>) -> TurnRecord) -> Nile {
+        let mut nile = Nile::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(4),
+        )
+        .unwrap();
+        for _ in 0..turn_count {
+            let turns = legal_turns_under(
+                nile.board(),
+                nile.current_player().tiles(),
+                RuleSet::default().river_check,
+            )
+            .collect();
+            nile.play_turn(&pick(turns)).unwrap();
+        }
+        nile
+    }
+
+    #[test]
+    fn played_turns_between_worst_and_best() {
+        let nile = game(4, |turns| {
+            TurnRecord::Placements(turns.into_iter().max_by_key(|t| t.len()).unwrap())
+        });
+        let target = GameAnalysis::new(&nile.record()).unwrap();
+        assert_eq!(target.turns().len(), 4);
+        for (turn, analysis) in target.turns().iter().enumerate() {
+            assert_eq!(analysis.turn, turn);
+            assert!(analysis.worst_score <= analysis.score);
+            assert!(analysis.score <= analysis.best_score);
+        }
+        // Players' accuracy is on the same scale as their turns'
+        for (player, player_analysis) in target.players().iter().enumerate() {
+            let turns: Vec<f32> = target
+                .turns()
+                .iter()
+                .filter(|t| t.player == player)
+                .map(|t| t.accuracy())
+                .collect();
+            let expected = turns.iter().sum::<f32>() / turns.len() as f32;
+            assert!((player_analysis.accuracy - expected).abs() < 1e-6);
+            assert!((0.0..=1.0).contains(&player_analysis.accuracy));
+        }
+        let replay = Replay::new(nile.record()).unwrap();
+        let first = &target.turns()[0];
+        assert_eq!(first.player, replay.nile().current_turn());
+        match &first.best {
+            TurnRecord::Placements(best) => assert_eq!(
+                turn_score_under(
+                    replay.nile().board(),
+                    replay.nile().current_player().tiles(),
+                    best,
+                    &nile.record().rules
+                )
+                .score(),
+                first.best_score
+            ),
+            TurnRecord::CantPlay => panic!("The first turn always has legal turns"),
+        }
+    }
+
+    #[test]
+    fn cant_play_with_legal_turns_is_a_blunder() {
+        // Both players claiming “can’t play” ends the game
+        let nile = game(2, |_| TurnRecord::CantPlay);
+        assert!(nile.has_ended());
+        let target = GameAnalysis::new(&nile.record()).unwrap();
+        for turn in target.turns() {
+            assert!(matches!(turn.best, TurnRecord::Placements(_)));
+            assert_eq!(turn.accuracy(), 0.0);
+            let player = &target.players()[turn.player];
+            assert_eq!(player.turns, 1);
+            assert_eq!(player.missed, turn.missed());
+            assert_eq!(player.accuracy, 0.0);
+        }
+        let blunders = target.blunders(2);
+        assert_eq!(blunders.len(), 2);
+        assert!(blunders[0].missed() >= blunders[1].missed());
+        assert_eq!(target.blunders(1), blunders[..1]);
+    }
+
+    #[test]
+    fn turn_accuracy() {
+        let mut target = TurnAnalysis {
+            turn: 0,
+            player: 0,
+            score: 30,
+            best: TurnRecord::CantPlay,
+            best_score: 50,
+            worst_score: 10,
+        };
+        assert_eq!(target.accuracy(), 0.5);
+        assert_eq!(target.missed(), 20);
+        target.score = 50;
+        assert_eq!(target.accuracy(), 1.0);
+        assert_eq!(target.missed(), 0);
+        target.worst_score = 50;
+        assert_eq!(target.accuracy(), 1.0);
+    }
+}
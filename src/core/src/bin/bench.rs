@@ -0,0 +1,173 @@
+//! Times move generation on late-game boards.
+//!
+//! Plays seeded games between `Brute` CPU players, keeps the boards and racks of the last turns
+//! of each game, then times generating every legal turn for them. Late-game boards are the
+//! slowest because the river is long and many turns have to be checked for encirclement.
+//!
+//! It also times the encirclement check itself on the boards after those turns, both the flood
+//! fill and the clone-based search it replaced.
+//!
+//! ```sh
+//! cargo run --release --features bench --bin bench -- --games 20 --turns 10
+//! ```
+use nile::{
+    legal_turns, legal_turns_under, Board, BoardLayout, Brute, Nile, PlayerSetup, RiverCheck,
+    RuleSet, Tile, TilePlacement,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use std::time::Instant;
+
+mod common;
+use common::parse_number;
+
+/// Most turns from each position whose boards the encirclement check is timed on
+const TURNS_PER_POSITION: usize = 100;
+
+const USAGE: &str = "Usage: bench [--games N] [--turns N] [--rounds N] [--seed N]";
+
+struct Options {
+    games: usize,
+    /// Turns kept from the end of each game
+    turns: usize,
+    /// Times every board is searched
+    rounds: usize,
+    seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            games: 20,
+            turns: 10,
+            rounds: 5,
+            seed: 0,
+        }
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+    common::parse_args(USAGE, |arg, value| {
+        match arg {
+            "--games" => options.games = parse_number(arg, value)? as usize,
+            "--turns" => options.turns = parse_number(arg, value)? as usize,
+            "--rounds" => options.rounds = parse_number(arg, value)?.max(1) as usize,
+            "--seed" => options.seed = parse_number(arg, value)?,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+        Ok(())
+    });
+    options
+}
+
+/// Boards and racks at the start of the last `turns` turns of a game
+fn late_game_positions(seed: u64, turns: usize) -> Vec<(Board, Vec<Tile>)> {
+    let players = (1..=2)
+        .map(|i| PlayerSetup::cpu(format!("cpu{}", i), Box::new(Brute::new(2))))
+        .collect();
+    let mut nile = Nile::new(
+        players,
+        BoardLayout::default(),
+        RuleSet::default(),
+        Some(seed),
+    )
+    .expect("valid game setup");
+    let mut positions = Vec::new();
+    while !nile.has_ended() {
+        positions.push((nile.board().clone(), nile.current_player().tiles().to_vec()));
+        nile.play_cpu_turn().expect("CPU turn");
+    }
+    let skip = positions.len().saturating_sub(turns);
+    positions.split_off(skip)
+}
+
+/// Boards after the turns from `positions` that leave the river somewhere to go. Some of them can
+/// no longer reach the end-of-game column, which is what the encirclement check decides
+fn turn_ends(positions: &[(Board, Vec<Tile>)]) -> Vec<Board> {
+    let mut ends = Vec::new();
+    for (board, rack) in positions {
+        for turn in legal_turns_under(board, rack, RiverCheck::Escape).take(TURNS_PER_POSITION) {
+            let mut end = board.clone();
+            for placement in turn.iter() {
+                end.place_tile(
+                    placement.coordinates,
+                    TilePlacement::new(placement.tile_path_type, placement.rotation),
+                )
+                .expect("legal turns place tiles on empty cells");
+            }
+            end.validate_turns_moves(
+                turn.iter().map(|p| p.coordinates).collect(),
+                RiverCheck::Escape,
+            )
+            .expect("legal turns can be played");
+            ends.push(end);
+        }
+    }
+    ends
+}
+
+/// Runs `check` on every board `rounds` times. Returns the milliseconds per board and how many
+/// boards passed the check
+fn time_check(boards: &[Board], rounds: usize, check: fn(&Board) -> bool) -> (f64, usize) {
+    let start = Instant::now();
+    let mut passed = 0;
+    for _ in 0..rounds {
+        passed = boards.iter().filter(|board| check(board)).count();
+    }
+    let checks = (boards.len() * rounds).max(1);
+    (
+        start.elapsed().as_secs_f64() * 1000.0 / checks as f64,
+        passed,
+    )
+}
+
+fn main() {
+    let options = parse_args();
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let positions: Vec<_> = (0..options.games)
+        .flat_map(|_| late_game_positions(rng.gen(), options.turns))
+        .collect();
+    eprintln!("searching {} late-game positions", positions.len());
+
+    let start = Instant::now();
+    let mut turn_count = 0;
+    for _ in 0..options.rounds {
+        for (board, rack) in positions.iter() {
+            turn_count += legal_turns(board, rack).count();
+        }
+    }
+    let elapsed = start.elapsed();
+    let searches = (positions.len() * options.rounds).max(1);
+    println!(
+        "{} searches found {} turns in {:.3}s: {:.3}ms per position",
+        searches,
+        turn_count,
+        elapsed.as_secs_f64(),
+        elapsed.as_secs_f64() * 1000.0 / searches as f64
+    );
+
+    let ends = turn_ends(&positions);
+    let (clone_millis, clone_passed) = time_check(
+        &ends,
+        options.rounds,
+        Board::can_reach_end_column_by_cloning,
+    );
+    let (flood_millis, flood_passed) =
+        time_check(&ends, options.rounds, Board::can_reach_end_column);
+    if clone_passed != flood_passed {
+        eprintln!(
+            "warning: the clone-based check passed {} boards, the flood fill {}",
+            clone_passed, flood_passed
+        );
+    }
+    println!(
+        "encirclement check on {} boards, {} of them open: clone-based {:.4}ms, flood fill {:.4}ms per board ({:.1}x)",
+        ends.len(),
+        flood_passed,
+        clone_millis,
+        flood_millis,
+        clone_millis / flood_millis.max(f64::MIN_POSITIVE)
+    );
+}
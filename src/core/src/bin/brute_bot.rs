@@ -0,0 +1,24 @@
+//! Reference bot for the stdin/stdout protocol in `nile::protocol`. Plays as the expert `Brute`
+//! CPU player.
+//!
+//! ```sh
+//! cargo build --release --bin brute_bot
+//! ```
+use nile::{run_bot, Brute};
+
+use std::io;
+use std::process::exit;
+
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = run_bot(
+        "brute",
+        |player_count| Box::new(Brute::new(player_count)),
+        stdin.lock(),
+        stdout.lock(),
+    ) {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
@@ -0,0 +1,70 @@
+//! Command-line parsing and threading shared by the binaries
+// Each binary uses only some of these
+#![allow(dead_code)]
+
+use rand::Rng;
+
+use std::process::exit;
+use std::thread;
+
+/// Calls `apply` with each `--name value` pair of the command line. Prints `usage` and exits for
+/// `-h` or `--help`, or when an argument is invalid
+pub fn parse_args(usage: &str, mut apply: impl FnMut(&str, &str) -> Result<(), String>) {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", usage);
+            exit(0);
+        }
+        let result = match args.next() {
+            Some(value) => apply(&arg, &value),
+            None => Err(format!("Missing value for {}", arg)),
+        };
+        if let Err(e) = result {
+            exit_with_usage(&e, usage);
+        }
+    }
+}
+
+/// Prints `message` and `usage` and exits with an error
+pub fn exit_with_usage(message: &str, usage: &str) -> ! {
+    eprintln!("{}\n\n{}", message, usage);
+    exit(1);
+}
+
+pub fn parse_number(arg: &str, value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|e| format!("Invalid value for {}: {}", arg, e))
+}
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Seeds for `games` games when each seed is played from both seats, so `games` is rounded up to
+/// an even number
+pub fn paired_seeds(rng: &mut impl Rng, games: usize) -> Vec<u64> {
+    (0..games.div_ceil(2).max(1)).map(|_| rng.gen()).collect()
+}
+
+/// Maps `items` with `f`, split into a chunk for each of `threads`. Results are in the order of
+/// `items`
+pub fn map_in_threads<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let chunk_size = items.len().div_ceil(threads).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("game thread"))
+            .collect()
+    })
+}
@@ -0,0 +1,289 @@
+//! Plays a round-robin tournament between CPU players and reports their standings.
+//!
+//! Every pair of bots plays the same seeded games, each seed once from each seat. Standings are
+//! printed to stdout and, with `--csv`, the result of every game is written as CSV.
+//!
+//! ```sh
+//! cargo run --release --bin tournament -- --bots expert,hard,lookahead --games 200 --csv games.csv
+//! ```
+//!
+//! Bots that speak the protocol in `nile::protocol` join with `external:<command>`, e.g.
+//! `--bots expert,external:target/release/brute_bot`. Each game starts its own process.
+use nile::{
+    BoardLayout, Brute, CPUPlayer, Difficulty, ExternalBot, Lookahead, MonteCarlo, Nile,
+    PlayerSetup, RuleSet, SearchBudget, DIFFICULTIES,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::exit;
+
+mod common;
+use common::{default_threads, map_in_threads, paired_seeds, parse_number};
+
+/// Rollouts per candidate turn of the Monte Carlo bot
+const MONTE_CARLO_ROLLOUTS: usize = 20;
+
+const USAGE: &str =
+    "Usage: tournament [--bots NAME,NAME,...] [--games N] [--threads N] [--seed N] \
+     [--csv PATH]\n\nBots: easy, medium, hard, expert, lookahead, montecarlo, \
+     external:<command>";
+
+#[derive(Clone, Debug, PartialEq)]
+enum Bot {
+    Brute(Difficulty),
+    Lookahead,
+    MonteCarlo,
+    /// A program and its arguments
+    External(String),
+}
+
+impl Bot {
+    fn parse(name: &str) -> Result<Self, String> {
+        if let Some(command) = name.strip_prefix("external:") {
+            return Ok(Self::External(command.to_owned()));
+        }
+        match name {
+            "lookahead" => Ok(Self::Lookahead),
+            "montecarlo" => Ok(Self::MonteCarlo),
+            _ => DIFFICULTIES
+                .iter()
+                .find(|d| d.to_string().to_lowercase() == name)
+                .map(|d| Self::Brute(*d))
+                .ok_or_else(|| format!("Unknown bot {}", name)),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Brute(difficulty) => difficulty.to_string().to_lowercase(),
+            Self::Lookahead => "lookahead".to_owned(),
+            Self::MonteCarlo => "montecarlo".to_owned(),
+            Self::External(command) => command.clone(),
+        }
+    }
+
+    fn strategy(&self, player_count: usize, seed: u64) -> Box<dyn CPUPlayer> {
+        match self {
+            Self::Brute(difficulty) => {
+                Box::new(Brute::with_difficulty(player_count, *difficulty, seed))
+            }
+            Self::Lookahead => {
+                Box::new(Lookahead::new(player_count, SearchBudget::default(), seed))
+            }
+            Self::MonteCarlo => Box::new(MonteCarlo::new(player_count, MONTE_CARLO_ROLLOUTS, seed)),
+            Self::External(command) => {
+                let mut words = command.split_whitespace();
+                let program = words.next().unwrap_or_default();
+                let args: Vec<&str> = words.collect();
+                Box::new(ExternalBot::spawn(program, &args).unwrap_or_else(|e| panic!("{}", e)))
+            }
+        }
+    }
+}
+
+struct Options {
+    bots: Vec<Bot>,
+    /// Games per pair of bots, with seeds from `paired_seeds`
+    games: usize,
+    threads: usize,
+    seed: u64,
+    csv: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            bots: vec![Bot::Brute(Difficulty::Hard), Bot::Brute(Difficulty::Expert)],
+            games: 100,
+            threads: default_threads(),
+            seed: 0,
+            csv: None,
+        }
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+    common::parse_args(USAGE, |arg, value| {
+        match arg {
+            "--bots" => {
+                options.bots = value.split(',').map(Bot::parse).collect::<Result<_, _>>()?;
+            }
+            "--games" => options.games = parse_number(arg, value)? as usize,
+            "--threads" => options.threads = parse_number(arg, value)?.max(1) as usize,
+            "--seed" => options.seed = parse_number(arg, value)?,
+            "--csv" => options.csv = Some(value.to_owned()),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+        Ok(())
+    });
+    if options.bots.len() < 2 {
+        common::exit_with_usage("Need at least two bots", USAGE);
+    }
+    options
+}
+
+/// A game between two bots, identified by their index in `Options::bots`
+#[derive(Copy, Clone)]
+struct Game {
+    seed: u64,
+    /// Seating order
+    bots: [usize; 2],
+}
+
+struct GameResult {
+    game: Game,
+    scores: [i16; 2],
+    /// Committed turns of both players
+    turns: usize,
+}
+
+fn play_game(bots: &[Bot], game: Game) -> GameResult {
+    let players = game
+        .bots
+        .iter()
+        .enumerate()
+        .map(|(seat, bot_idx)| {
+            let bot = &bots[*bot_idx];
+            // Two copies of the same bot don't make the same choices
+            PlayerSetup::cpu(
+                format!("{}{}", bot.name(), seat + 1),
+                bot.strategy(2, game.seed ^ seat as u64),
+            )
+        })
+        .collect();
+    let mut nile = Nile::new(
+        players,
+        BoardLayout::default(),
+        RuleSet::default(),
+        Some(game.seed),
+    )
+    .expect("valid game setup");
+    while !nile.has_ended() {
+        nile.play_cpu_turn().expect("CPU turn");
+    }
+    GameResult {
+        game,
+        scores: [
+            nile.players()[0].total_score(),
+            nile.players()[1].total_score(),
+        ],
+        turns: nile.turns().len(),
+    }
+}
+
+fn play_games(bots: &[Bot], games: &[Game], threads: usize) -> Vec<GameResult> {
+    map_in_threads(games, threads, |game| play_game(bots, *game))
+}
+
+#[derive(Default)]
+struct Standing {
+    games: usize,
+    wins: usize,
+    ties: usize,
+    total_score: i64,
+    total_turns: usize,
+}
+
+fn standings(bot_count: usize, results: &[GameResult]) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = (0..bot_count).map(|_| Standing::default()).collect();
+    for result in results {
+        for seat in 0..2 {
+            let standing = &mut standings[result.game.bots[seat]];
+            let (score, other_score) = (result.scores[seat], result.scores[1 - seat]);
+            standing.games += 1;
+            standing.wins += (score > other_score) as usize;
+            standing.ties += (score == other_score) as usize;
+            standing.total_score += i64::from(score);
+            standing.total_turns += result.turns;
+        }
+    }
+    standings
+}
+
+fn print_standings(bots: &[Bot], standings: &[Standing]) {
+    let mut ranking: Vec<usize> = (0..bots.len()).collect();
+    let win_rate = |s: &Standing| (s.wins as f64 + s.ties as f64 / 2.0) / s.games.max(1) as f64;
+    ranking.sort_by(|x, y| win_rate(&standings[*y]).total_cmp(&win_rate(&standings[*x])));
+    println!(
+        "{:<4} {:<12} {:>6} {:>6} {:>6} {:>6} {:>9} {:>10} {:>10}",
+        "rank", "bot", "games", "wins", "ties", "losses", "win rate", "avg score", "avg turns"
+    );
+    for (rank, idx) in ranking.into_iter().enumerate() {
+        let standing = &standings[idx];
+        let games = standing.games.max(1) as f64;
+        println!(
+            "{:<4} {:<12} {:>6} {:>6} {:>6} {:>6} {:>8.1}% {:>10.1} {:>10.1}",
+            rank + 1,
+            bots[idx].name(),
+            standing.games,
+            standing.wins,
+            standing.ties,
+            standing.games - standing.wins - standing.ties,
+            win_rate(standing) * 100.0,
+            standing.total_score as f64 / games,
+            standing.total_turns as f64 / games,
+        );
+    }
+}
+
+fn write_csv(path: &str, bots: &[Bot], results: &[GameResult]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "game,seed,bot1,bot2,score1,score2,winner,turns")?;
+    for (i, result) in results.iter().enumerate() {
+        let names = result.game.bots.map(|idx| bots[idx].name());
+        let winner = match result.scores[0].cmp(&result.scores[1]) {
+            std::cmp::Ordering::Greater => names[0].as_str(),
+            std::cmp::Ordering::Less => names[1].as_str(),
+            std::cmp::Ordering::Equal => "",
+        };
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            i + 1,
+            result.game.seed,
+            names[0],
+            names[1],
+            result.scores[0],
+            result.scores[1],
+            winner,
+            result.turns
+        )?;
+    }
+    writer.flush()
+}
+
+fn main() {
+    let options = parse_args();
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let seeds = paired_seeds(&mut rng, options.games);
+    let bot_count = options.bots.len();
+    let mut games = Vec::new();
+    for first in 0..bot_count {
+        for second in first + 1..bot_count {
+            for seed in seeds.iter() {
+                games.push(Game {
+                    seed: *seed,
+                    bots: [first, second],
+                });
+                games.push(Game {
+                    seed: *seed,
+                    bots: [second, first],
+                });
+            }
+        }
+    }
+    eprintln!("playing {} games", games.len());
+
+    let results = play_games(&options.bots, &games, options.threads);
+    print_standings(&options.bots, &standings(bot_count, &results));
+    if let Some(path) = options.csv {
+        if let Err(e) = write_csv(&path, &options.bots, &results) {
+            eprintln!("Failed to write {}: {}", path, e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Tunes the `AiWeights` of `Brute` by self-play.
+//!
+//! Starting from the default weights, each generation mutates the current best weights and plays
+//! a match between the two. The challenger replaces the champion when the lower end of the 95%
+//! confidence interval of its win rate is above 50%. The interval is computed over seeds rather
+//! than games because the two games played on each seed aren't independent.
+//!
+//! Only the win rate gets a confidence interval. The printed weights are point estimates with no
+//! interval of their own.
+//!
+//! ```sh
+//! cargo run --release --bin tune -- --generations 50 --games 2000 --threads 8 --seed 1
+//! ```
+use nile::{AiWeights, BoardLayout, Brute, Engine, PlayerSetup, RuleSet};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::{default_threads, map_in_threads, paired_seeds, parse_number};
+
+struct Options {
+    generations: usize,
+    /// Games per match, with seeds from `paired_seeds`
+    games: usize,
+    threads: usize,
+    seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            generations: 20,
+            games: 1000,
+            threads: default_threads(),
+            seed: 0,
+        }
+    }
+}
+
+const USAGE: &str =
+    "Usage: tune [--generations N] [--games N] [--threads N] [--seed N]\n\nPrints the best weights \
+     found as JSON to stdout and progress to stderr";
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+    common::parse_args(USAGE, |arg, value| {
+        match arg {
+            "--generations" => options.generations = parse_number(arg, value)? as usize,
+            "--games" => options.games = parse_number(arg, value)? as usize,
+            "--threads" => options.threads = parse_number(arg, value)?.max(1) as usize,
+            "--seed" => options.seed = parse_number(arg, value)?,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+        Ok(())
+    });
+    options
+}
+
+/// Result of a match between a challenger and a champion
+struct MatchResult {
+    /// The challenger's win rate over the two games of each seed. Ties count as half a win
+    seed_win_rates: Vec<f64>,
+}
+
+impl MatchResult {
+    fn games(&self) -> usize {
+        self.seed_win_rates.len() * 2
+    }
+
+    fn win_rate(&self) -> f64 {
+        self.seed_win_rates.iter().sum::<f64>() / self.seed_win_rates.len() as f64
+    }
+
+    /// 95% confidence interval of the challenger's win rate using the normal approximation over
+    /// the seeds' win rates
+    fn confidence_interval(&self) -> (f64, f64) {
+        let n = self.seed_win_rates.len();
+        if n < 2 {
+            return (0.0, 1.0);
+        }
+        let mean = self.win_rate();
+        let variance = self
+            .seed_win_rates
+            .iter()
+            .map(|rate| (rate - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1) as f64;
+        let margin = 1.96 * (variance / n as f64).sqrt();
+        (mean - margin, mean + margin)
+    }
+}
+
+/// Plays a two-player game and returns the challenger's result: 1 for a win, 0.5 for a tie and
+/// 0 for a loss
+fn play_game(
+    challenger: &AiWeights,
+    champion: &AiWeights,
+    seed: u64,
+    challenger_first: bool,
+) -> f64 {
+    let challenger_setup = PlayerSetup::cpu(
+        "challenger".to_owned(),
+        Box::new(Brute::with_weights(2, challenger.clone())),
+    );
+    let champion_setup = PlayerSetup::cpu(
+        "champion".to_owned(),
+        Box::new(Brute::with_weights(2, champion.clone())),
+    );
+    let (players, challenger_idx) = if challenger_first {
+        (vec![challenger_setup, champion_setup], 0)
+    } else {
+        (vec![champion_setup, challenger_setup], 1)
+    };
+    let engine = Engine::new(
+        players,
+        BoardLayout::default(),
+        RuleSet::default(),
+        Some(seed),
+    )
+    .expect("valid game setup");
+    assert!(engine.has_ended(), "CPU-only games play to the end");
+    let challenger_score = engine.players()[challenger_idx].total_score();
+    let champion_score = engine.players()[1 - challenger_idx].total_score();
+    match challenger_score.cmp(&champion_score) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Less => 0.0,
+    }
+}
+
+/// Plays each seed from both seats, split across `threads`
+fn play_match(
+    challenger: &AiWeights,
+    champion: &AiWeights,
+    seeds: &[u64],
+    threads: usize,
+) -> MatchResult {
+    let seed_win_rates = map_in_threads(seeds, threads, |seed| {
+        (play_game(challenger, champion, *seed, true)
+            + play_game(challenger, champion, *seed, false))
+            / 2.0
+    });
+    MatchResult { seed_win_rates }
+}
+
+/// Changes one randomly-chosen weight by a step proportional to its usual magnitude
+fn mutate(weights: &AiWeights, rng: &mut impl Rng) -> AiWeights {
+    let mut mutated = weights.clone();
+    let (weight, step) = match rng.gen_range(0, 6) {
+        0 => (&mut mutated.end_game_winning, 200),
+        1 => (&mut mutated.end_game_losing, 50),
+        2 => (&mut mutated.next_tile, 1),
+        3 => (&mut mutated.universal_tile, 5),
+        4 => (&mut mutated.column_progress, 2),
+        _ => (&mut mutated.all_tiles, 5),
+    };
+    let delta = if rng.gen() { step } else { -step };
+    *weight = weight.saturating_add(delta);
+    mutated
+}
+
+fn main() {
+    let options = parse_args();
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+
+    let baseline = AiWeights::default();
+    let mut champion = baseline.clone();
+    for generation in 1..=options.generations {
+        let challenger = mutate(&champion, &mut rng);
+        let seeds = paired_seeds(&mut rng, options.games);
+        let result = play_match(&challenger, &champion, &seeds, options.threads);
+        let (low, high) = result.confidence_interval();
+        let is_accepted = low > 0.5;
+        eprintln!(
+            "generation {}: {} won {:.1}% (win rate 95% CI {:.1}%–{:.1}% over seeds) of {} games{}",
+            generation,
+            serde_json::to_string(&challenger).expect("weights serialize"),
+            result.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0,
+            result.games(),
+            if is_accepted { ", accepted" } else { "" }
+        );
+        if is_accepted {
+            champion = challenger;
+        }
+    }
+
+    // Final match on fresh seeds so the estimate isn't biased by the selection
+    if champion != baseline {
+        let seeds = paired_seeds(&mut rng, options.games);
+        let result = play_match(&champion, &baseline, &seeds, options.threads);
+        let (low, high) = result.confidence_interval();
+        eprintln!(
+            "best weights won {:.1}% (win rate 95% CI {:.1}%–{:.1}% over seeds) of {} games against the defaults",
+            result.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0,
+            result.games()
+        );
+    } else {
+        eprintln!("no weights were significantly better than the defaults");
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&champion).expect("weights serialize")
+    );
+}
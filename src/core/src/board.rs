@@ -1,12 +1,15 @@
 use crate::error::{self, CellError, Error};
+use crate::layout::{BoardLayout, CellBonus};
 use crate::log::TilePlacementEvent;
 use crate::path::{self, eval_placement, Offset, TilePath, TilePathType};
-use crate::score::TurnScore;
+use crate::rules::RiverCheck;
+use crate::score::{ScoreBreakdown, TurnScore};
 use crate::tile::{Coordinates, Rotation};
 
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Eq, PartialEq))]
 pub struct TilePlacement {
     tile_path_type: TilePathType,
@@ -30,19 +33,26 @@ impl TilePlacement {
         self.rotation
     }
 
-    pub fn offsets(&self) -> Vec<Offset> {
-        self.tile_path_type
-            .offsets()
-            .iter()
-            .map(|o| o.rotate(self.rotation))
-            .collect()
+    pub fn offsets(&self) -> [Offset; 2] {
+        self.tile_path_type.rotated_offsets(self.rotation)
     }
 }
 
-#[derive(Clone, Debug, Default)]
+/// Who placed a tile and in which turn
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlacedBy {
+    /// Index of the player
+    pub player: usize,
+    /// Index of the turn in `Nile::turns`, or `Nile::turns().len()` during the current turn
+    pub turn: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Cell {
     bonus: i16,
     tile: Option<TilePlacement>,
+    /// `None` for empty cells and tiles placed outside of a game, e.g. during a search
+    placed_by: Option<PlacedBy>,
 }
 
 impl Cell {
@@ -53,11 +63,19 @@ impl Cell {
     pub fn bonus(&self) -> i16 {
         self.bonus
     }
+
+    pub fn placed_by(&self) -> Option<PlacedBy> {
+        self.placed_by
+    }
 }
 
 impl Cell {
     pub fn with_bonus(bonus: i16) -> Self {
-        Self { bonus, tile: None }
+        Self {
+            bonus,
+            tile: None,
+            placed_by: None,
+        }
     }
 
     pub fn set_tile(&mut self, tile: TilePlacement) -> TurnScore {
@@ -67,6 +85,7 @@ impl Cell {
 
     pub fn remove_tile(&mut self) -> Option<(TilePlacement, TurnScore)> {
         let old_score = self.score();
+        self.placed_by = None;
         self.tile.take().map(|tp| (tp, -old_score))
     }
 
@@ -101,26 +120,75 @@ impl Cell {
 }
 
 /// The board is 21x21 plus a special end of game column
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SavedBoard")]
 pub struct Board {
     last_placement: (Coordinates, Offset),
     cells: Vec<Cell>,
     end_of_game_cells: Vec<Cell>,
+    /// Cells with a tile, kept in sync with `cells` and `end_of_game_cells` so searches can
+    /// check for tiles without going through `Cell`
+    #[serde(skip)]
+    occupied: CellSet,
 }
 
 pub const BOARD_DIM: usize = 21;
 
-macro_rules! hash_map(
-    { $($key:expr => $value:expr),+ } => {
-        {
-            let mut m = ::std::collections::HashMap::new();
-            $(
-                m.insert($key, $value);
-            )+
-            m
+/// A `Board` as it's saved. `occupied` is rebuilt from the cells when loading
+#[derive(Deserialize)]
+struct SavedBoard {
+    last_placement: (Coordinates, Offset),
+    cells: Vec<Cell>,
+    end_of_game_cells: Vec<Cell>,
+}
+
+impl From<SavedBoard> for Board {
+    fn from(saved: SavedBoard) -> Self {
+        let mut board = Self {
+            last_placement: saved.last_placement,
+            cells: saved.cells,
+            end_of_game_cells: saved.end_of_game_cells,
+            occupied: CellSet::default(),
+        };
+        let coordinates: Vec<Coordinates> = board.tiles().map(|(c, _)| c).collect();
+        for c in coordinates {
+            board.occupied.insert(c);
         }
-    };
-);
+        board
+    }
+}
+
+/// One bit for each cell of the board, including the end-of-game column
+#[derive(Clone, Debug, Default)]
+struct CellSet([u64; CellSet::WORDS]);
+
+impl CellSet {
+    const WORDS: usize = (BOARD_DIM * (BOARD_DIM + 1)).div_ceil(64);
+
+    /// `coordinates` must be in bounds
+    fn index(Coordinates(row, column): Coordinates) -> (usize, u64) {
+        let idx = row as usize * (BOARD_DIM + 1) + column as usize;
+        (idx / 64, 1 << (idx % 64))
+    }
+
+    fn contains(&self, coordinates: Coordinates) -> bool {
+        let (word, bit) = Self::index(coordinates);
+        self.0[word] & bit != 0
+    }
+
+    /// Returns whether `coordinates` wasn't already in the set
+    fn insert(&mut self, coordinates: Coordinates) -> bool {
+        let (word, bit) = Self::index(coordinates);
+        let is_new = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        is_new
+    }
+
+    fn remove(&mut self, coordinates: Coordinates) {
+        let (word, bit) = Self::index(coordinates);
+        self.0[word] &= !bit;
+    }
+}
 
 impl Default for Board {
     fn default() -> Self {
@@ -129,68 +197,31 @@ impl Default for Board {
 }
 
 impl Board {
+    /// Board with the standard layout
     pub fn new() -> Board {
-        let bonus_order: Vec<i16> = vec![50, 50, 50, 50, 75, 75, 100, 100, 150, 200, 500];
-        let bonuses: HashMap<(usize, usize), i16> = hash_map!(
-            (1, 6) => -80,
-            (1, 10) => -100,
-            (1, 14) => -120,
-            (2, 2) => -40,
-            (2, 3) => -40,
-            (2, 17) => -160,
-            (2, 18) => -160,
-            (3, 2) => -40,
-            (3, 8) => 140,
-            (3, 12) => 220,
-            (3, 18) => -160,
-            (4, 2) => -40,
-            (4, 3) => -40,
-            (4, 17) => -160,
-            (4, 18) => -160,
-            (5, 6) => -80,
-            (5, 10) => -100,
-            (5, 14) => -120,
-            (7, 2) => 60,
-            (7, 10) => 240,
-            (7, 18) => 250,
-            (8, 7) => 240,
-            (8, 13) => 240,
-            (9, 5) => -60,
-            (9, 9) => -40,
-            (9, 11) => -40,
-            (9, 15) => -140,
-            (10, 10) => -60,
-            (10, 18) => -160
-        );
-        let cells: Vec<Cell> = (0..BOARD_DIM * BOARD_DIM)
-            .map(|i| {
-                let row = {
-                    let row = i / BOARD_DIM;
-                    // Board is reflected across horizontal axis
-                    if row > 10 {
-                        BOARD_DIM - 1 - row
-                    } else {
-                        row
-                    }
-                };
-                let col = i % BOARD_DIM;
-                bonuses
-                    .get(&(row, col))
-                    .map(|b| Cell::with_bonus(*b))
-                    .unwrap_or_default()
-            })
-            .collect();
-        Self {
-            // Start arrow placement and offset
-            last_placement: (Coordinates(10, -1), Offset(0, 1)),
+        Self::from_layout(&BoardLayout::default()).expect("classic layout is valid")
+    }
+
+    pub fn from_layout(layout: &BoardLayout) -> Result<Board, String> {
+        layout.validate()?;
+        let mut cells = vec![Cell::default(); BOARD_DIM * BOARD_DIM];
+        for CellBonus {
+            coordinates: Coordinates(row, column),
+            bonus,
+        } in layout.bonuses.iter()
+        {
+            cells[*row as usize * BOARD_DIM + *column as usize] = Cell::with_bonus(*bonus);
+        }
+        Ok(Self {
+            last_placement: (layout.start.coordinates, layout.start.direction),
             cells,
-            // Symmetrical
-            end_of_game_cells: bonus_order
+            end_of_game_cells: layout
+                .end_column
                 .iter()
-                .chain(bonus_order.iter().rev().skip(1))
                 .map(|b| Cell::with_bonus(*b))
                 .collect(),
-        }
+            occupied: CellSet::default(),
+        })
     }
 
     pub fn cell(&self, coordinates: Coordinates) -> Option<&Cell> {
@@ -219,13 +250,24 @@ impl Board {
         }
     }
 
+    /// Returns the score of the placement
     pub fn place_tile(
         &mut self,
         coordinates: Coordinates,
         tile_placement: TilePlacement,
-    ) -> Result<TurnScore, CellError> {
+    ) -> Result<ScoreBreakdown, CellError> {
+        let is_end_column = self.is_end_game_cell(coordinates);
         match self.get_mut_cell(coordinates) {
-            Some(cell) if cell.is_empty() => Ok(cell.set_tile(tile_placement)),
+            Some(cell) if cell.is_empty() => {
+                let score = ScoreBreakdown::placement(
+                    tile_placement.tile_path_type.score(),
+                    cell.bonus(),
+                    is_end_column,
+                );
+                cell.set_tile(tile_placement);
+                self.occupied.insert(coordinates);
+                Ok(score)
+            }
             Some(_) => Err(CellError::new(
                 coordinates,
                 "There’s already a tile there".to_owned(),
@@ -237,6 +279,25 @@ impl Board {
         }
     }
 
+    /// Like `place_tile`, also recording who placed the tile
+    pub(crate) fn place_tile_by(
+        &mut self,
+        coordinates: Coordinates,
+        tile_placement: TilePlacement,
+        placed_by: PlacedBy,
+    ) -> Result<ScoreBreakdown, CellError> {
+        let score = self.place_tile(coordinates, tile_placement)?;
+        self.set_placed_by(coordinates, Some(placed_by));
+        Ok(score)
+    }
+
+    /// Records who placed the tile at `coordinates`. Does nothing for empty cells
+    pub(crate) fn set_placed_by(&mut self, coordinates: Coordinates, placed_by: Option<PlacedBy>) {
+        if let Some(cell) = self.get_mut_cell(coordinates).filter(|c| !c.is_empty()) {
+            cell.placed_by = placed_by;
+        }
+    }
+
     pub fn rotate_tile(
         &mut self,
         coordinates: Coordinates,
@@ -253,11 +314,19 @@ impl Board {
         }
     }
 
-    pub fn remove_tile(&mut self, coordinates: Coordinates) -> Option<(TilePlacement, TurnScore)> {
-        match self.get_mut_cell(coordinates) {
-            Some(cell) => cell.remove_tile(),
-            None => None,
-        }
+    /// Returns the removed tile and the score that reverts its placement
+    pub fn remove_tile(
+        &mut self,
+        coordinates: Coordinates,
+    ) -> Option<(TilePlacement, ScoreBreakdown)> {
+        let is_end_column = self.is_end_game_cell(coordinates);
+        let cell = self.get_mut_cell(coordinates)?;
+        let bonus = cell.bonus();
+        let (tile_placement, _) = cell.remove_tile()?;
+        self.occupied.remove(coordinates);
+        let score =
+            ScoreBreakdown::placement(tile_placement.tile_path_type.score(), bonus, is_end_column);
+        Some((tile_placement, -score))
     }
 
     /// Returns the old `TilePath`
@@ -278,7 +347,8 @@ impl Board {
         &mut self,
         old_coordinates: Coordinates,
         new_coordinates: Coordinates,
-    ) -> Result<TurnScore, CellError> {
+    ) -> Result<ScoreBreakdown, CellError> {
+        let placed_by = self.cell(old_coordinates).and_then(Cell::placed_by);
         let (tile_placement, removal_score) =
             self.remove_tile(old_coordinates).ok_or_else(|| {
                 CellError::new(
@@ -288,24 +358,70 @@ impl Board {
             })?;
         let placement_score = self
             .place_tile(new_coordinates, tile_placement.clone())
-            .map_err(|e| {
+            .inspect_err(|_| {
                 // Try to replace the tile
                 self.place_tile(old_coordinates, tile_placement)
                     .expect("should be able to replace tile");
-                e
+                self.set_placed_by(old_coordinates, placed_by);
             })?;
+        self.set_placed_by(new_coordinates, placed_by);
         // `removal_score` should already be negative to revert the score effect of placement
         Ok(placement_score + removal_score)
     }
 
+    /// All tiles on the board, including the end-of-game column, with their coordinates
+    pub fn tiles(&self) -> impl Iterator<Item = (Coordinates, &TilePlacement)> {
+        let board_tiles = self.cells.iter().enumerate().filter_map(|(i, cell)| {
+            cell.tile.as_ref().map(|tp| {
+                (
+                    Coordinates((i / BOARD_DIM) as i8, (i % BOARD_DIM) as i8),
+                    tp,
+                )
+            })
+        });
+        let end_of_game_tiles =
+            self.end_of_game_cells
+                .iter()
+                .enumerate()
+                .filter_map(|(row, cell)| {
+                    cell.tile
+                        .as_ref()
+                        .map(|tp| (Coordinates(row as i8, BOARD_DIM as i8), tp))
+                });
+        board_tiles.chain(end_of_game_tiles)
+    }
+
+    /// Checks the dimensions of a board that wasn't created by `Board::new`, e.g. one that was
+    /// loaded from a save
+    pub(crate) fn validate_dimensions(&self) -> Result<(), String> {
+        if self.cells.len() != BOARD_DIM * BOARD_DIM {
+            Err(format!(
+                "Expected {} board cells, found {}",
+                BOARD_DIM * BOARD_DIM,
+                self.cells.len()
+            ))
+        } else if self.end_of_game_cells.len() != BOARD_DIM {
+            Err(format!(
+                "Expected {} end-of-game cells, found {}",
+                BOARD_DIM,
+                self.end_of_game_cells.len()
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn last_placement(&self) -> (Coordinates, Offset) {
         self.last_placement
     }
 
+    /// Used when rebuilding a board from a description of it rather than from its turns
+    pub(crate) fn set_last_placement(&mut self, last_placement: (Coordinates, Offset)) {
+        self.last_placement = last_placement;
+    }
+
     pub fn has_tile(&self, coordinates: Coordinates) -> bool {
-        self.cell(coordinates)
-            .map(|cell| !cell.is_empty())
-            .unwrap_or_default()
+        self.in_bounds(coordinates) && self.occupied.contains(coordinates)
     }
 
     pub fn is_end_game_cell(&self, coordinates: Coordinates) -> bool {
@@ -318,6 +434,7 @@ impl Board {
     pub fn validate_turns_moves(
         &mut self,
         mut turn_coordinates: HashSet<Coordinates>,
+        river_check: RiverCheck,
     ) -> crate::error::Result<bool> {
         let mut last_placement = self.last_placement;
         while !turn_coordinates.is_empty() {
@@ -348,6 +465,18 @@ impl Board {
                 ));
             }
         }
+        let has_ended = self.validate_turn_end(last_placement, river_check)?;
+        self.last_placement = last_placement;
+        Ok(has_ended)
+    }
+
+    /// Checks the rules that apply to the river as a whole once all of a turn's tiles have been
+    /// placed and the river ends at `last_placement`. Returns whether the game has ended
+    pub(crate) fn validate_turn_end(
+        &self,
+        last_placement: (Coordinates, Offset),
+        river_check: RiverCheck,
+    ) -> crate::error::Result<bool> {
         // Check last tile doesn't end in another tile
         if self.has_tile(last_placement.0 + last_placement.1) {
             let mut err_coordinates = HashSet::new();
@@ -369,10 +498,9 @@ impl Board {
             .filter(|c| !c.is_empty())
             .count();
         // Check this turns doesn't leave the river encircled
-        self.no_encircles(last_placement).map_err(Error::Cell)?;
-        let has_ended = Self::validate_end_of_game_cells(end_of_game_cell_count, last_placement)?;
-        self.last_placement = last_placement;
-        Ok(has_ended)
+        self.no_encircles(last_placement, river_check)
+            .map_err(Error::Cell)?;
+        Self::validate_end_of_game_cells(end_of_game_cell_count, last_placement)
     }
 
     pub fn in_bounds(&self, coordinates: Coordinates) -> bool {
@@ -421,107 +549,178 @@ impl Board {
         coordinates: Coordinates,
         offset: Offset,
     ) -> crate::error::Result<()> {
+        if self.crosses_over(coordinates, offset) {
+            let coordinates1 = coordinates + Offset(offset.0, 0);
+            let coordinates2 = coordinates + Offset(0, offset.1);
+            let mut err_coordinates = HashSet::new();
+            err_coordinates.insert(coordinates);
+            err_coordinates.insert(coordinates1);
+            err_coordinates.insert(coordinates2);
+            return Err(Error::cells(err_coordinates, format!("The river cannot cross over existing path between {} and {}. Invalid tile placement at {}", coordinates1, coordinates2, coordinates)));
+        }
+        Ok(())
+    }
+
+    /// `no_crossover` without building an error, for searches that check many placements
+    pub(crate) fn crosses_over(&self, coordinates: Coordinates, offset: Offset) -> bool {
         if !offset.is_diagonal() {
-            return Ok(());
+            return false;
         }
         let coordinates1 = coordinates + Offset(offset.0, 0);
         let coordinates2 = coordinates + Offset(0, offset.1);
-        if let (Some(tp1), Some(tp2)) = (
-            self.cell(coordinates1).and_then(|c| c.tile.as_ref()),
-            self.cell(coordinates2).and_then(|c| c.tile.as_ref()),
-        ) {
-            if tp1
-                .offsets()
-                .into_iter()
-                .any(|o| coordinates1 + o == coordinates2)
-                && tp2
-                    .offsets()
-                    .into_iter()
-                    .any(|o| coordinates2 + o == coordinates1)
-            {
-                let mut err_coordinates = HashSet::new();
-                err_coordinates.insert(coordinates);
-                err_coordinates.insert(coordinates1);
-                err_coordinates.insert(coordinates2);
-                return Err(Error::cells(err_coordinates, format!("The river cannot cross over existing path between {} and {}. Invalid tile placement at {}", coordinates1, coordinates2, coordinates)));
-            }
+        if !self.has_tile(coordinates1) || !self.has_tile(coordinates2) {
+            return false;
         }
-        Ok(())
+        let connects = |from: Coordinates, to: Coordinates| {
+            self.cell(from)
+                .and_then(|c| c.tile.as_ref())
+                .is_some_and(|tp| tp.offsets().into_iter().any(|o| from + o == to))
+        };
+        connects(coordinates1, coordinates2) && connects(coordinates2, coordinates1)
     }
 
-    /// Determines if river is completely encircled and there is not 'escape'.
-    /// This incidates one or more moves are invalid
-    ///
-    /// Parameterizing `turn_coordinates` and `last_placement` allows this
-    /// function to be more easily used by the AI in determining which potential
-    /// moves are valid. This method is _almost_ static and can easily be tested
-    /// with an empty board and all state passed in via the arguments.
-    fn open_moves(&self) -> Vec<Offset> {
-        let next_coordinates = self.last_placement.0 + self.last_placement.1;
+    /// Empty cells the river could flow into from `coordinates`
+    fn open_moves(&self, coordinates: Coordinates) -> Vec<Offset> {
         path::OFFSETS
             .iter()
-            .filter_map(|offset| {
-                if self.no_crossover(next_coordinates, *offset).is_err() {
-                    return None;
-                }
-                let coordinates = next_coordinates + *offset;
-                match self.cell(coordinates) {
-                    Some(cell) if cell.is_empty() => Some(*offset),
-                    _ => None,
-                }
+            .filter(|offset| {
+                self.cell(coordinates + **offset).is_some()
+                    && !self.has_tile(coordinates + **offset)
+                    && !self.crosses_over(coordinates, **offset)
             })
+            .copied()
             .collect()
     }
 
-    fn no_encircles(&self, last_placement: (Coordinates, Offset)) -> Result<(), CellError> {
-        let (last_coordinates, _) = last_placement;
-        if self.is_end_game_cell(last_coordinates) {
-            return Ok(());
-        }
-        let mut visited = HashSet::new();
-        self.no_encircles_impl(last_placement, &mut visited)
+    /// Whether the game can still end with a tile in the end-of-game column, i.e. some path of
+    /// legal tile placements leads there from the end of the river
+    pub fn can_reach_end_column(&self) -> bool {
+        self.is_end_game_cell(self.last_placement.0) || self.reaches_end_column(self.last_placement)
+    }
+
+    /// `can_reach_end_column` the way the encirclement check worked before the flood fill: a
+    /// depth-first search that places a tile on a copy of the board for every step. Only kept so
+    /// `bench` can compare the two
+    #[cfg(any(test, feature = "bench"))]
+    pub fn can_reach_end_column_by_cloning(&self) -> bool {
+        self.is_end_game_cell(self.last_placement.0)
+            || self.reaches_by_cloning(self.last_placement, &mut HashSet::new())
     }
 
-    fn no_encircles_impl(
+    #[cfg(any(test, feature = "bench"))]
+    fn reaches_by_cloning(
         &self,
         last_placement: (Coordinates, Offset),
         visited: &mut HashSet<Coordinates>,
-    ) -> Result<(), CellError> {
+    ) -> bool {
         let (last_coordinates, last_offset) = last_placement;
         let coordinates = last_coordinates + last_offset;
         if self.is_end_game_cell(coordinates) {
-            return Ok(());
+            return true;
+        }
+        if self.cell(coordinates).is_none() {
+            return false;
         }
         visited.insert(coordinates);
         let mut copy = self.clone();
         copy.last_placement = last_placement;
-        copy.cell(coordinates).ok_or_else(|| {
-            CellError::new(
-                coordinates,
-                format!(
-                    "Invalid river path leading to coordinates: {} that are off the board",
-                    coordinates
-                ),
-            )
-        })?;
-        let open_moves = copy.open_moves();
-
-        for offset in open_moves.iter() {
-            if visited.contains(&(coordinates + *offset)) {
+        for offset in copy.open_moves(coordinates) {
+            if visited.contains(&(coordinates + offset)) {
                 continue;
             }
-            if let Some(tp) = path::offsets_to_tile_placement(last_offset, *offset) {
+            if let Some(tp) = path::offsets_to_tile_placement(last_offset, offset) {
                 if copy.place_tile(coordinates, tp).is_ok() {
-                    match copy.no_encircles_impl((coordinates, *offset), visited) {
-                        Ok(()) => return Ok(()),
-                        Err(_) => {
-                            copy.remove_tile(coordinates);
-                        }
+                    if copy.reaches_by_cloning((coordinates, offset), visited) {
+                        return true;
                     }
+                    copy.remove_tile(coordinates);
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether the river's next cell has an empty neighbor that a tile on it could lead to
+    fn has_escape(&self, last_placement: (Coordinates, Offset)) -> bool {
+        let (last_coordinates, last_offset) = last_placement;
+        let coordinates = last_coordinates + last_offset;
+        self.is_end_game_cell(coordinates)
+            || self
+                .open_moves(coordinates)
+                .into_iter()
+                .any(|offset| path::offsets_to_tile_placement(last_offset, offset).is_some())
+    }
+
+    /// Whether the river, ending at `last_placement`, can still be extended to the end-of-game
+    /// column. Flood fills the empty cells reachable from the river's next cell without crossing
+    /// over existing tiles. A shortest path through the filled cells never crosses itself and
+    /// never turns back on itself, so there's a tile for each of its cells
+    pub(crate) fn reaches_end_column(&self, last_placement: (Coordinates, Offset)) -> bool {
+        let (last_coordinates, last_offset) = last_placement;
+        let start = last_coordinates + last_offset;
+        if self.cell(start).is_none() || self.has_tile(start) {
+            return false;
+        }
+        if self.is_end_game_cell(start) {
+            return true;
+        }
+        let mut visited = CellSet::default();
+        visited.insert(start);
+        // The tile in the next cell has to connect to the river
+        let mut stack: Vec<Coordinates> = self
+            .open_moves(start)
+            .into_iter()
+            .filter(|offset| path::offsets_to_tile_placement(last_offset, *offset).is_some())
+            .map(|offset| start + offset)
+            .collect();
+        for coordinates in stack.iter() {
+            visited.insert(*coordinates);
+        }
+        while let Some(coordinates) = stack.pop() {
+            if self.is_end_game_cell(coordinates) {
+                return true;
+            }
+            // Pushing the moves toward the end-of-game column last explores them first
+            for offset in path::OFFSETS.iter().rev() {
+                let next = coordinates + *offset;
+                if self.cell(next).is_some()
+                    && !self.has_tile(next)
+                    && !self.crosses_over(coordinates, *offset)
+                    && visited.insert(next)
+                {
+                    stack.push(next);
                 }
             }
         }
-        Err(CellError::new(coordinates, format!("Encircled river path. There are no paths leading to the end of game column from {} with open moves {:?}", coordinates, open_moves)))
+        false
+    }
+
+    /// Determines if river is completely encircled and there is not 'escape'.
+    /// This incidates one or more moves are invalid
+    fn no_encircles(
+        &self,
+        last_placement: (Coordinates, Offset),
+        river_check: RiverCheck,
+    ) -> Result<(), CellError> {
+        let (last_coordinates, last_offset) = last_placement;
+        let is_open = match river_check {
+            RiverCheck::Escape => self.has_escape(last_placement),
+            RiverCheck::Reachable => self.reaches_end_column(last_placement),
+        };
+        if self.is_end_game_cell(last_coordinates) || is_open {
+            return Ok(());
+        }
+        let coordinates = last_coordinates + last_offset;
+        if self.cell(coordinates).is_none() {
+            return Err(CellError::new(
+                coordinates,
+                format!(
+                    "Invalid river path leading to coordinates: {} that are off the board",
+                    coordinates
+                ),
+            ));
+        }
+        Err(CellError::new(coordinates, format!("Encircled river path. There are no paths leading to the end of game column from {} with open moves {:?}", coordinates, self.open_moves(coordinates))))
     }
 }
 
@@ -538,6 +737,7 @@ impl Board {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::layout::StartArrow;
 
     use std::iter::FromIterator;
 
@@ -581,6 +781,7 @@ mod test {
                 tile_path_type: TilePathType::Universal(TilePath::Diagonal),
             }),
             bonus: 80,
+            placed_by: None,
         };
         assert_eq!(target.score().score(), 115);
     }
@@ -612,6 +813,7 @@ mod test {
                 tile_path_type: TilePathType::Normal(TilePath::Left45),
             }),
             bonus: 0,
+            placed_by: None,
         };
         let res = target.update_universal_path(TilePath::Right45);
         assert!(matches!(res, Err(e) if e.contains("doesn’t contain a universal tile")));
@@ -625,6 +827,7 @@ mod test {
                 tile_path_type: TilePathType::Universal(TilePath::Left45),
             }),
             bonus: 0,
+            placed_by: None,
         };
         let res = target.update_universal_path(TilePath::Right45);
         assert!(matches!(res, Ok(TilePath::Left45)));
@@ -652,10 +855,49 @@ mod test {
             .unwrap();
         let mut coordinates_set = HashSet::new();
         coordinates_set.insert(coordinates);
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn custom_layout() {
+        let layout = BoardLayout {
+            bonuses: vec![CellBonus {
+                coordinates: Coordinates(0, 3),
+                bonus: 30,
+            }],
+            start: StartArrow {
+                coordinates: Coordinates(-1, 3),
+                direction: Offset(1, 0),
+            },
+            ..BoardLayout::default()
+        };
+        let mut target = Board::from_layout(&layout).unwrap();
+        assert_eq!(target.cell(Coordinates(0, 3)).unwrap().bonus(), 30);
+        assert_eq!(target.cell(Coordinates(3, 8)).unwrap().bonus(), 0);
+        let coordinates = Coordinates(0, 3);
+        target
+            .place_tile(
+                coordinates,
+                TilePlacement {
+                    rotation: Rotation::Clockwise90,
+                    tile_path_type: TilePathType::Normal(TilePath::Straight),
+                },
+            )
+            .unwrap();
+        let res = target
+            .validate_turns_moves(HashSet::from_iter(vec![coordinates]), RiverCheck::default());
+        assert!(res.is_ok());
+        assert_eq!(target.last_placement(), (Coordinates(0, 3), Offset(1, 0)));
+    }
+
+    #[test]
+    fn invalid_layout() {
+        let mut layout = BoardLayout::default();
+        layout.start.coordinates = Coordinates(10, 0);
+        assert!(Board::from_layout(&layout).is_err());
+    }
+
     #[test]
     fn validate_45() {
         let mut target = Board::new();
@@ -677,14 +919,14 @@ mod test {
         let mut coordinates_set = HashSet::new();
         coordinates_set.insert(Coordinates(10, 0));
         coordinates_set.insert(Coordinates(11, 0));
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(res.is_ok());
     }
 
     #[test]
     fn no_crossover() {
         let mut target = Board::with_last_placement(Coordinates(4, 2), Offset(1, -1));
-        let coordinates = vec![Coordinates(5, 1), Coordinates(6, 0), Coordinates(6, 1)];
+        let coordinates = [Coordinates(5, 1), Coordinates(6, 0), Coordinates(6, 1)];
         target
             .place_tile(
                 coordinates[0],
@@ -710,14 +952,14 @@ mod test {
             )
             .unwrap();
         let coordinates_set = HashSet::from_iter(coordinates.iter().cloned());
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(matches!(res, Err(Error::Cell(err)) if err.msg.contains("cross over")));
     }
 
     #[test]
     fn universal_start() {
         let mut target = Board::new();
-        let coordinates = vec![
+        let coordinates = [
             Coordinates(10, 0),
             Coordinates(11, 1),
             Coordinates(12, 2),
@@ -767,14 +1009,14 @@ mod test {
             )
             .unwrap();
         let coordinates_set = HashSet::from_iter(coordinates.iter().cloned());
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(matches!(res, Ok(false)));
     }
 
     #[test]
     fn crossover_is_invalid() {
         let mut target = Board::new();
-        let coordinates = vec![
+        let coordinates = [
             Coordinates(14, 5),
             Coordinates(14, 4),
             Coordinates(14, 3),
@@ -824,14 +1066,14 @@ mod test {
             )
             .unwrap();
         let coordinates_set = HashSet::from_iter(coordinates.iter().cloned());
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(res.is_err());
     }
 
     #[test]
     fn end_with_dead_end() {
         let mut target = Board::new();
-        let coordinates = vec![
+        let coordinates = [
             Coordinates(6, 2),
             Coordinates(7, 3),
             Coordinates(7, 2),
@@ -871,7 +1113,7 @@ mod test {
             )
             .unwrap();
         let coordinates_set = HashSet::from_iter(coordinates.iter().cloned());
-        let res = target.validate_turns_moves(coordinates_set);
+        let res = target.validate_turns_moves(coordinates_set, RiverCheck::default());
         assert!(res.is_err());
     }
 
@@ -912,7 +1154,7 @@ mod test {
     #[test]
     fn cell_out_of_bounds_cell_is_none() {
         let target = Board::new();
-        assert!(matches!(target.cell(Coordinates(14, 22)), None));
+        assert!(target.cell(Coordinates(14, 22)).is_none());
     }
 
     #[test]
@@ -929,7 +1171,7 @@ mod test {
             )
             .unwrap();
         let turn_coordinates = hash_set!(COORDINATES);
-        let res = target.validate_turns_moves(turn_coordinates);
+        let res = target.validate_turns_moves(turn_coordinates, RiverCheck::default());
         assert!(matches!(res, Ok(true)));
     }
 
@@ -1040,7 +1282,7 @@ mod test {
                 },
             )
             .unwrap();
-        let res = target.no_encircles((Coordinates(2, 1), Offset(-1, -1)));
+        let res = target.no_encircles((Coordinates(2, 1), Offset(-1, -1)), RiverCheck::default());
         assert!(res.is_err());
 
         target = setup_encircled_board();
@@ -1053,7 +1295,7 @@ mod test {
                 },
             )
             .unwrap();
-        let res = target.no_encircles((Coordinates(2, 1), Offset(-1, 0)));
+        let res = target.no_encircles((Coordinates(2, 1), Offset(-1, 0)), RiverCheck::default());
         assert!(res.is_err());
 
         target = setup_encircled_board();
@@ -1063,7 +1305,7 @@ mod test {
                 TilePlacement::new(TilePathType::Normal(TilePath::Right135), Rotation::None),
             )
             .unwrap();
-        let res = target.no_encircles((Coordinates(2, 1), Offset(1, 0)));
+        let res = target.no_encircles((Coordinates(2, 1), Offset(1, 0)), RiverCheck::default());
         assert!(res.is_ok());
     }
 
@@ -1112,7 +1354,7 @@ mod test {
                 TilePlacement::new(TilePathType::Normal(TilePath::Center90), Rotation::None),
             )
             .unwrap();
-        let res = target.no_encircles((Coordinates(12, 1), Offset(0, -1)));
+        let res = target.no_encircles((Coordinates(12, 1), Offset(0, -1)), RiverCheck::default());
         assert!(res.is_ok());
     }
 
@@ -1137,7 +1379,107 @@ mod test {
                 TilePlacement::new(TilePathType::Normal(TilePath::Straight), Rotation::None),
             )
             .unwrap();
-        let res = target.no_encircles((Coordinates(12, 21), Offset(0, 1)));
+        let res = target.no_encircles((Coordinates(12, 21), Offset(0, 1)), RiverCheck::default());
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn reaches_end_column_through_gap() {
+        // A wall of vertical tiles across column 5, except for one row
+        let wall = |gap: Option<i8>| {
+            let mut board = Board::new();
+            for row in (0..BOARD_DIM as i8).filter(|row| Some(*row) != gap) {
+                board
+                    .place_tile(
+                        Coordinates(row, 5),
+                        TilePlacement::new(
+                            TilePathType::Normal(TilePath::Straight),
+                            Rotation::Clockwise90,
+                        ),
+                    )
+                    .unwrap();
+            }
+            board
+        };
+        let last_placement = (Coordinates(10, 2), Offset(0, 1));
+        assert!(!wall(None).reaches_end_column(last_placement));
+        assert!(wall(None)
+            .no_encircles(last_placement, RiverCheck::default())
+            .is_err());
+        assert!(wall(Some(0)).reaches_end_column(last_placement));
+        assert!(wall(Some(20))
+            .no_encircles(last_placement, RiverCheck::default())
+            .is_ok());
+        // Off the board
+        assert!(!Board::new().reaches_end_column((Coordinates(0, 2), Offset(-1, 0))));
+        // The check it replaced agrees
+        for gap in [None, Some(0), Some(10), Some(20)] {
+            let mut board = wall(gap);
+            board.set_last_placement(last_placement);
+            assert_eq!(
+                board.can_reach_end_column_by_cloning(),
+                board.can_reach_end_column()
+            );
+        }
+    }
+
+    #[test]
+    fn river_check_escape_allows_boxed_in_river() {
+        // The river flows up column 2 into a pocket walled off by row 1 and column 5
+        let mut target = Board::new();
+        let straight =
+            |rotation| TilePlacement::new(TilePathType::Normal(TilePath::Straight), rotation);
+        for row in 1..BOARD_DIM as i8 {
+            target
+                .place_tile(Coordinates(row, 5), straight(Rotation::Clockwise90))
+                .unwrap();
+        }
+        for column in 0..5 {
+            target
+                .place_tile(Coordinates(1, column), straight(Rotation::None))
+                .unwrap();
+        }
+        target
+            .place_tile(Coordinates(0, 5), straight(Rotation::Clockwise90))
+            .unwrap();
+        target
+            .place_tile(Coordinates(3, 2), straight(Rotation::Clockwise90))
+            .unwrap();
+        let last_placement = (Coordinates(3, 2), Offset(-1, 0));
+        assert!(target
+            .no_encircles(last_placement, RiverCheck::Escape)
+            .is_ok());
+        assert!(target
+            .no_encircles(last_placement, RiverCheck::Reachable)
+            .is_err());
+
+        target.set_last_placement(last_placement);
+        assert!(!target.can_reach_end_column());
+        target.set_last_placement((Coordinates(3, 6), Offset(0, 1)));
+        assert!(target.can_reach_end_column());
+    }
+
+    #[test]
+    fn occupancy_follows_tiles() {
+        let mut target = Board::new();
+        let tile = TilePlacement::new(TilePathType::Normal(TilePath::Straight), Rotation::None);
+        target.place_tile(Coordinates(3, 4), tile.clone()).unwrap();
+        target.place_tile(Coordinates(7, 21), tile).unwrap();
+        target
+            .move_tile(Coordinates(3, 4), Coordinates(3, 5))
+            .unwrap();
+        assert!(!target.has_tile(Coordinates(3, 4)));
+        assert!(target.has_tile(Coordinates(3, 5)));
+        assert!(target.has_tile(Coordinates(7, 21)));
+
+        // Rebuilt when loaded
+        let loaded: Board = serde_json::from_str(&serde_json::to_string(&target).unwrap()).unwrap();
+        assert!(loaded.has_tile(Coordinates(3, 5)));
+        assert!(loaded.has_tile(Coordinates(7, 21)));
+        assert!(!loaded.has_tile(Coordinates(3, 4)));
+
+        target.remove_tile(Coordinates(7, 21));
+        assert!(!target.has_tile(Coordinates(7, 21)));
+        assert!(target.remove_tile(Coordinates(-1, 21)).is_none());
+    }
 }
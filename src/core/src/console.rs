@@ -1,5 +1,7 @@
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     // Use `js_namespace` here to bind `console.log(..)` instead of just
@@ -15,3 +17,30 @@ extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     pub fn error(s: &str);
 }
+
+// Calling the JS bindings outside of the browser panics, so write to stderr instead. This allows
+// the game logic to be run natively, e.g. in tests
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(s: &str) {
+    eprintln!("{}", s);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn debug(s: &str) {
+    eprintln!("DEBUG: {}", s);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn info(s: &str) {
+    eprintln!("INFO: {}", s);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn warn(s: &str) {
+    eprintln!("WARN: {}", s);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn error(s: &str) {
+    eprintln!("ERROR: {}", s);
+}
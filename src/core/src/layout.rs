@@ -0,0 +1,186 @@
+use crate::board::BOARD_DIM;
+use crate::path::Offset;
+use crate::tile::Coordinates;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The standard Nile board
+const CLASSIC: &str = include_str!("../layouts/classic.json");
+
+/// Definition of the bonus and penalty cells, the start arrow, and the end-of-game column of a
+/// board. All layouts share the same `BOARD_DIM` x `BOARD_DIM` size
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardLayout {
+    /// Cells with a nonzero bonus. All other cells have no bonus
+    pub bonuses: Vec<CellBonus>,
+    pub start: StartArrow,
+    /// Bonus of each cell in the end-of-game column from top to bottom
+    pub end_column: Vec<i16>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CellBonus {
+    pub coordinates: Coordinates,
+    /// Negative for penalties
+    pub bonus: i16,
+}
+
+/// Where the river begins. `coordinates` is off the board and the first tile is placed at
+/// `coordinates + direction`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartArrow {
+    pub coordinates: Coordinates,
+    pub direction: Offset,
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self {
+        serde_json::from_str(CLASSIC).expect("classic layout is valid JSON")
+    }
+}
+
+impl BoardLayout {
+    /// Parses and validates a layout in JSON. See `layouts/classic.json` for an example
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let layout: Self =
+            serde_json::from_str(json).map_err(|e| format!("Invalid board layout: {}", e))?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to save layout: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let on_board = |Coordinates(row, column): Coordinates| {
+            (0..BOARD_DIM as i8).contains(&row) && (0..BOARD_DIM as i8).contains(&column)
+        };
+        let mut seen = HashSet::new();
+        for CellBonus { coordinates, .. } in self.bonuses.iter() {
+            if !on_board(*coordinates) {
+                return Err(format!("Bonus at {} is off the board", coordinates));
+            }
+            if !seen.insert(*coordinates) {
+                return Err(format!("Multiple bonuses at {}", coordinates));
+            }
+        }
+        let StartArrow {
+            coordinates,
+            direction,
+        } = self.start;
+        let Offset(row_offset, column_offset) = direction;
+        if !(-1..=1).contains(&row_offset)
+            || !(-1..=1).contains(&column_offset)
+            || direction == Offset(0, 0)
+        {
+            return Err(format!(
+                "Start arrow direction ({}, {}) must point to an adjacent cell",
+                row_offset, column_offset
+            ));
+        }
+        if on_board(coordinates) {
+            return Err(format!(
+                "Start arrow at {} must be just off the board, not on it",
+                coordinates
+            ));
+        }
+        if !on_board(coordinates + direction) {
+            return Err(format!(
+                "Start arrow at {} must point to a cell on the board, not {}",
+                coordinates,
+                coordinates + direction
+            ));
+        }
+        if self.end_column.len() != BOARD_DIM {
+            return Err(format!(
+                "Expected {} end-of-game column values, found {}",
+                BOARD_DIM,
+                self.end_column.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classic_is_valid() {
+        let target = BoardLayout::default();
+        assert!(target.validate().is_ok());
+        assert_eq!(BoardLayout::from_json(CLASSIC), Ok(target));
+    }
+
+    #[test]
+    fn round_trip() {
+        let target = BoardLayout::default();
+        assert_eq!(
+            BoardLayout::from_json(&target.to_json().unwrap()),
+            Ok(target)
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        let res = BoardLayout::from_json("{\"bonuses\": []}");
+        assert!(matches!(res, Err(msg) if msg.starts_with("Invalid board layout")));
+    }
+
+    #[test]
+    fn off_board_start_arrow() {
+        let mut target = BoardLayout::default();
+        target.start.coordinates = Coordinates(25, -1);
+        assert!(target.validate().is_err());
+        target.start.coordinates = Coordinates(10, 0);
+        assert!(target.validate().is_err());
+        // Points away from the board
+        target.start = StartArrow {
+            coordinates: Coordinates(10, -1),
+            direction: Offset(0, -1),
+        };
+        assert!(target.validate().is_err());
+    }
+
+    #[test]
+    fn start_arrow_from_top() {
+        let target = BoardLayout {
+            start: StartArrow {
+                coordinates: Coordinates(-1, 3),
+                direction: Offset(1, 1),
+            },
+            ..BoardLayout::default()
+        };
+        assert!(target.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_start_direction() {
+        let mut target = BoardLayout::default();
+        target.start.direction = Offset(0, 2);
+        assert!(target.validate().is_err());
+    }
+
+    #[test]
+    fn invalid_bonuses() {
+        let mut target = BoardLayout::default();
+        target.bonuses.push(CellBonus {
+            coordinates: Coordinates(3, BOARD_DIM as i8),
+            bonus: 10,
+        });
+        assert!(target.validate().is_err());
+        target.bonuses.pop();
+        target.bonuses.push(target.bonuses[0].clone());
+        assert!(target.validate().is_err());
+    }
+
+    #[test]
+    fn wrong_end_column_length() {
+        let mut target = BoardLayout::default();
+        target.end_column.pop();
+        assert!(target.validate().is_err());
+    }
+}
@@ -1,20 +1,42 @@
 mod ai;
+mod analysis;
 mod board;
 pub mod console;
 mod error;
+mod layout;
 mod log;
+mod moves;
 mod nile;
+mod notation;
+mod parallel;
 mod path;
 mod player;
+mod protocol;
+mod record;
+mod replay;
+mod rules;
+mod save;
 mod score;
 mod tile;
 
-#[macro_use]
-extern crate wasm_bindgen;
-
-pub use crate::board::{Board, Cell, TilePlacement, BOARD_DIM};
-pub use crate::nile::{Engine, Nile, SelectedTile};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::ai::ExternalBot;
+pub use crate::ai::{
+    AiWeights, Brute, CPUPlayer, CloneCPUPlayer, Difficulty, Lookahead, MonteCarlo, SearchBudget,
+    StrategyState, DIFFICULTIES,
+};
+pub use crate::analysis::{GameAnalysis, PlayerAnalysis, TurnAnalysis};
+pub use crate::board::{Board, Cell, PlacedBy, TilePlacement, BOARD_DIM};
+pub use crate::layout::{BoardLayout, CellBonus, StartArrow};
+pub use crate::log::TilePlacementEvent;
+pub use crate::moves::{legal_turns, legal_turns_under, turn_score};
+pub use crate::nile::{Engine, Hint, Nile, SelectedTile};
+pub use crate::notation::NotationError;
 pub use crate::path::{TilePath, TilePathType, TILE_PATHS};
-pub use crate::player::{Player, TileArray};
-pub use crate::score::TurnScore;
-pub use crate::tile::{Coordinates, Rotation, Tile, ROTATIONS};
+pub use crate::player::{Player, PlayerSetup, TileArray};
+pub use crate::protocol::{run_bot, Position, PROTOCOL_VERSION};
+pub use crate::record::{GameRecord, RecordedPlayer, TurnRecord};
+pub use crate::replay::Replay;
+pub use crate::rules::{AllTilesBonus, CantPlayCheck, CantPlayPenalty, RiverCheck, RuleSet};
+pub use crate::score::{FinalStandings, ScoreBreakdown, Standing, TurnScore};
+pub use crate::tile::{random_seed, Coordinates, Rotation, Tile, ROTATIONS};
@@ -1,30 +1,32 @@
 use crate::path::{TilePath, TilePathType};
 use crate::tile::{self, Coordinates};
 
+use serde::{Deserialize, Serialize};
+
 /// Represents the action of placing a single tile on the board. Several other
 /// event types could be compacted into a single `TilePlacementEvent` because
 /// they simply modify or undo a `TilePlacementEvent`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TilePlacementEvent {
     pub tile_path_type: TilePathType,
     pub coordinates: Coordinates,
     pub rotation: tile::Rotation,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Rotation {
     pub coordinates: Coordinates,
     pub rotation: tile::Rotation,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UpdateUniversalPathEvent {
     pub coordinates: Coordinates,
     pub old_tile_path: TilePath,
     pub new_tile_path: TilePath,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RevertableEvent<T: Clone> {
     pub old: T,
     pub new: T,
@@ -35,7 +37,7 @@ type MoveTileEvent = RevertableEvent<Coordinates>;
 
 /// Internal representation of a user event. All information necessary for
 /// undoing a `Event` is self-contained.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) enum Event {
     PlaceTile(TilePlacementEvent),
     RotateTile(RotationEvent),
@@ -73,7 +75,7 @@ impl Event {
 }
 
 /// Game event log
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Log {
     /// Immutable events of past turns
     events: Vec<Event>,
@@ -0,0 +1,401 @@
+use crate::board::{Board, TilePlacement};
+use crate::error;
+use crate::log::TilePlacementEvent;
+use crate::parallel;
+use crate::path::{exit_offset, Offset, TilePath, TilePathType, TILE_PATHS};
+use crate::rules::{RiverCheck, RuleSet};
+use crate::score::TurnScore;
+use crate::tile::{Coordinates, Rotation, Tile, ROTATIONS};
+
+/// Every legal turn that can be played on `board` with the tiles in `rack`, i.e. every sequence
+/// of placements that `Board::validate_turns_moves` would accept. Turns are generated lazily in
+/// depth-first order, so each turn is directly followed by the turns that extend it.
+///
+/// Identical tiles in the rack and rotations of symmetrical tiles that result in the same river
+/// are only generated once.
+pub fn legal_turns(board: &Board, rack: &[Tile]) -> impl Iterator<Item = Vec<TilePlacementEvent>> {
+    legal_turns_under(board, rack, RiverCheck::default())
+}
+
+/// `legal_turns` where turns are accepted according to `river_check`
+pub fn legal_turns_under(
+    board: &Board,
+    rack: &[Tile],
+    river_check: RiverCheck,
+) -> impl Iterator<Item = Vec<TilePlacementEvent>> {
+    let board = board.clone();
+    let last_placement = board.last_placement();
+    let root = Frame::new(&board, last_placement, rack.to_vec());
+    LegalTurns {
+        board,
+        river_check,
+        stack: vec![root],
+        placements: Vec::new(),
+    }
+}
+
+/// `legal_turns_under(board, rack, river_check).map(f).collect()`. With the `parallel` feature,
+/// turns that start with different placements are generated and mapped on separate threads
+pub(crate) fn map_legal_turns<T, F>(
+    board: &Board,
+    rack: &[Tile],
+    river_check: RiverCheck,
+    f: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Vec<TilePlacementEvent>) -> T + Sync + Send,
+{
+    let board = board.clone();
+    let last_placement = board.last_placement();
+    let root = Frame::new(&board, last_placement, rack.to_vec());
+    // One search per first placement, in the order the whole search would try them
+    let searches = root
+        .candidates
+        .iter()
+        .map(|candidate| LegalTurns {
+            board: board.clone(),
+            river_check,
+            stack: vec![Frame {
+                rack: root.rack.clone(),
+                candidates: vec![candidate.clone()],
+                next_candidate: 0,
+                placed: None,
+            }],
+            placements: Vec::new(),
+        })
+        .collect();
+    parallel::map(searches, |search| search.map(&f).collect::<Vec<_>>())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// The score a turn earns, including tile scores, cell bonuses and penalties, and the bonus for
+/// playing all the tiles in `rack`. `board` should be the board before the turn is played
+pub fn turn_score(board: &Board, rack: &[Tile], turn: &[TilePlacementEvent]) -> TurnScore {
+    turn_score_under(board, rack, turn, &RuleSet::default())
+}
+
+/// `turn_score` where the bonus for playing all the tiles is determined by `rules`
+pub(crate) fn turn_score_under(
+    board: &Board,
+    rack: &[Tile],
+    turn: &[TilePlacementEvent],
+    rules: &RuleSet,
+) -> TurnScore {
+    let score = turn.iter().fold(TurnScore::default(), |score, placement| {
+        let bonus = board
+            .cell(placement.coordinates)
+            .map(|cell| cell.bonus())
+            .unwrap_or_default();
+        score + TurnScore::from(placement.tile_path_type.score()) + TurnScore::from(bonus)
+    });
+    score + TurnScore::from(rules.all_tiles_bonus(rack.len(), turn.len()))
+}
+
+/// Places `turn` on `board` and advances the end of the river, checking the river according to
+/// `river_check`. Returns whether the turn ended the game
+pub(crate) fn play_turn(
+    board: &mut Board,
+    turn: &[TilePlacementEvent],
+    river_check: RiverCheck,
+) -> error::Result<bool> {
+    for placement in turn {
+        board
+            .place_tile(
+                placement.coordinates,
+                TilePlacement::new(placement.tile_path_type, placement.rotation),
+            )
+            .map_err(error::Error::Cell)?;
+    }
+    board.validate_turns_moves(turn.iter().map(|p| p.coordinates).collect(), river_check)
+}
+
+struct LegalTurns {
+    /// Copy of the board with the placements of the turn being explored
+    board: Board,
+    river_check: RiverCheck,
+    stack: Vec<Frame>,
+    placements: Vec<TilePlacementEvent>,
+}
+
+/// One tile of the turn being explored
+struct Frame {
+    /// Tiles that haven't been placed by earlier frames
+    rack: Vec<Tile>,
+    /// Placements that align with the river and don't immediately break a rule, with the index
+    /// in `rack` of the tile and where the river ends after the placement
+    candidates: Vec<(usize, TilePlacementEvent, (Coordinates, Offset))>,
+    next_candidate: usize,
+    /// Coordinates of the candidate currently placed on the board
+    placed: Option<Coordinates>,
+}
+
+fn tile_path_types(tile: Tile) -> Vec<TilePathType> {
+    match tile {
+        Tile::Universal => TILE_PATHS
+            .iter()
+            .map(|tp| TilePathType::Universal(*tp))
+            .collect(),
+        _ => vec![TilePathType::from(tile)],
+    }
+}
+
+/// Straight and diagonal only have two effective rotations because they're symmetrical
+fn rotations(tile_path_type: TilePathType) -> &'static [Rotation] {
+    match TilePath::from(&tile_path_type) {
+        TilePath::Straight | TilePath::Diagonal => &ROTATIONS[..2],
+        _ => &ROTATIONS[..],
+    }
+}
+
+impl Frame {
+    fn new(board: &Board, last_placement: (Coordinates, Offset), rack: Vec<Tile>) -> Self {
+        let coordinates = last_placement.0 + last_placement.1;
+        let mut candidates = Vec::new();
+        if !board.has_tile(coordinates) && board.cell(coordinates).is_some() {
+            for (idx, tile) in rack.iter().enumerate() {
+                // Identical tiles result in identical turns
+                if rack[..idx].contains(tile) {
+                    continue;
+                }
+                for tile_path_type in tile_path_types(*tile) {
+                    for rotation in rotations(tile_path_type) {
+                        let placement = TilePlacementEvent {
+                            tile_path_type,
+                            coordinates,
+                            rotation: *rotation,
+                        };
+                        if let Some(next) = Self::check_placement(board, last_placement, &placement)
+                        {
+                            candidates.push((idx, placement, next));
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            rack,
+            candidates,
+            next_candidate: 0,
+            placed: None,
+        }
+    }
+
+    /// Rules that can be checked tile by tile. These prune the search because no later tile can
+    /// make up for breaking them
+    fn check_placement(
+        board: &Board,
+        last_placement: (Coordinates, Offset),
+        placement: &TilePlacementEvent,
+    ) -> Option<(Coordinates, Offset)> {
+        let coordinates = placement.coordinates;
+        let offset = exit_offset(
+            last_placement.0,
+            coordinates,
+            TilePath::from(&placement.tile_path_type),
+            placement.rotation,
+        )?;
+        let next_coordinates = coordinates + offset;
+        // The river can't lead off the board, except from the end-of-game column where it ends
+        if board.cell(next_coordinates).is_none() && !board.is_end_game_cell(coordinates) {
+            return None;
+        }
+        // Dead-ends into the rest of the river
+        if board.has_tile(next_coordinates) {
+            return None;
+        }
+        if board.crosses_over(coordinates, offset) {
+            return None;
+        }
+        Some((coordinates, offset))
+    }
+}
+
+impl Iterator for LegalTurns {
+    type Item = Vec<TilePlacementEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            if let Some(coordinates) = frame.placed.take() {
+                self.board.remove_tile(coordinates);
+                self.placements.pop();
+            }
+            let (idx, placement, last_placement) = match frame.candidates.get(frame.next_candidate)
+            {
+                Some(candidate) => candidate.clone(),
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            frame.next_candidate += 1;
+            frame.placed = Some(placement.coordinates);
+            self.board
+                .place_tile(
+                    placement.coordinates,
+                    TilePlacement::new(placement.tile_path_type, placement.rotation),
+                )
+                .expect("candidate cell is empty");
+            self.placements.push(placement);
+
+            let is_legal = self
+                .board
+                .validate_turn_end(last_placement, self.river_check)
+                .is_ok();
+            // Tiles in the end-of-game column must be the last of the river. Later tiles can't
+            // free a river that can no longer reach the end-of-game column, but they can extend
+            // it where that's allowed
+            if frame.rack.len() > 1
+                && !self.board.is_end_game_cell(last_placement.0)
+                && (is_legal
+                    || self.river_check == RiverCheck::Escape
+                    || self.board.reaches_end_column(last_placement))
+            {
+                let mut rack = frame.rack.clone();
+                rack.remove(idx);
+                let child = Frame::new(&self.board, last_placement, rack);
+                self.stack.push(child);
+            }
+            if is_legal {
+                return Some(self.placements.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::eval_placement;
+
+    fn validate(board: &Board, turn: &[TilePlacementEvent]) -> error::Result<bool> {
+        play_turn(&mut board.clone(), turn, RiverCheck::default())
+    }
+
+    #[test]
+    fn first_turn_single_tile() {
+        let board = Board::new();
+        let turns: Vec<_> = legal_turns(&board, &[Tile::Straight]).collect();
+        assert_eq!(
+            turns,
+            vec![vec![TilePlacementEvent {
+                tile_path_type: TilePathType::Normal(TilePath::Straight),
+                coordinates: Coordinates(10, 0),
+                rotation: Rotation::None,
+            }]]
+        );
+    }
+
+    #[test]
+    fn all_turns_are_valid() {
+        let board = Board::new();
+        let rack = [
+            Tile::Universal,
+            Tile::Left45,
+            Tile::Corner90,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        let mut count = 0;
+        for turn in legal_turns(&board, &rack) {
+            assert!(validate(&board, &turn).is_ok(), "{:?}", turn);
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn no_duplicate_turns() {
+        let board = Board::new();
+        let rack = [Tile::Straight, Tile::Straight, Tile::Diagonal];
+        let turns: Vec<_> = legal_turns(&board, &rack).collect();
+        for (i, turn) in turns.iter().enumerate() {
+            assert!(!turns[i + 1..].contains(turn), "{:?}", turn);
+        }
+        // Diagonal can't follow the start arrow or a straight tile
+        assert_eq!(turns.len(), 2);
+    }
+
+    #[test]
+    fn map_matches_legal_turns_order() {
+        let board = Board::new();
+        let rack = [
+            Tile::Universal,
+            Tile::Left45,
+            Tile::Corner90,
+            Tile::Straight,
+            Tile::Straight,
+        ];
+        let expected: Vec<_> = legal_turns(&board, &rack).collect();
+        assert_eq!(
+            map_legal_turns(&board, &rack, RiverCheck::default(), |turn| turn),
+            expected
+        );
+    }
+
+    #[test]
+    fn no_turns_off_board() {
+        let board = Board::with_last_placement(Coordinates(19, 0), Offset(1, 0));
+        let rack = [Tile::Straight, Tile::Diagonal, Tile::Straight];
+        assert_eq!(legal_turns(&board, &rack).count(), 0);
+    }
+
+    #[test]
+    fn end_of_game_column() {
+        let mut board = Board::new();
+        let mut last_placement = board.last_placement();
+        let mut turn = Vec::new();
+        for column in 0..20 {
+            let placement = TilePlacementEvent {
+                tile_path_type: TilePathType::Normal(TilePath::Straight),
+                coordinates: Coordinates(10, column),
+                rotation: Rotation::None,
+            };
+            last_placement = eval_placement(last_placement, &placement).unwrap();
+            board
+                .place_tile(
+                    placement.coordinates,
+                    TilePlacement::new(placement.tile_path_type, placement.rotation),
+                )
+                .unwrap();
+            turn.push(placement);
+        }
+        board
+            .validate_turns_moves(
+                turn.iter().map(|p| p.coordinates).collect(),
+                RiverCheck::default(),
+            )
+            .unwrap();
+        assert_eq!(last_placement, board.last_placement());
+        let rack = [Tile::Straight, Tile::Straight, Tile::Straight];
+        let turns: Vec<_> = legal_turns(&board, &rack).collect();
+        // Can't continue past the end-of-game column
+        assert_eq!(turns.len(), 2);
+        assert!(turns
+            .iter()
+            .all(|turn| validate(&board, turn).is_ok() && turn.len() <= 2));
+        assert!(matches!(validate(&board, &turns[1]), Ok(true)));
+    }
+
+    #[test]
+    fn score_of_turn() {
+        let board = Board::new();
+        let turn = vec![TilePlacementEvent {
+            tile_path_type: TilePathType::Universal(TilePath::Straight),
+            coordinates: Coordinates(10, 0),
+            rotation: Rotation::None,
+        }];
+        assert_eq!(
+            turn_score(&board, &[Tile::Universal, Tile::Straight], &turn),
+            TurnScore::from(Tile::Universal.score())
+        );
+        assert_eq!(
+            turn_score(&board, &[Tile::Universal], &turn),
+            TurnScore::from(Tile::Universal.score())
+                + TurnScore::from(RuleSet::default().all_tiles_bonus)
+        );
+    }
+}
@@ -2,14 +2,21 @@ use std::collections::HashSet;
 use std::rc::Rc;
 
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::ai::{Brute, CPUPlayer};
-use crate::board::{Board, TilePlacement};
+use crate::board::{Board, PlacedBy, TilePlacement};
 use crate::error::{self, Error};
-use crate::log::{Event, Log};
-use crate::path::{TilePath, TilePathType};
-use crate::player::Player;
-use crate::tile::{Coordinates, Rotation, Tile, TileBox};
+use crate::layout::BoardLayout;
+use crate::log::{Event, Log, TilePlacementEvent};
+use crate::moves::{legal_turns_under, turn_score_under};
+use crate::path::{eval_placement, Offset, TilePath, TilePathType};
+use crate::player::{Player, PlayerSetup, TileArray};
+use crate::record::{GameRecord, RecordedPlayer, TurnRecord};
+use crate::rules::{CantPlayCheck, RuleSet};
+use crate::save;
+use crate::score::{FinalStandings, TurnScore};
+use crate::tile::{random_seed, Coordinates, GameRng, Rotation, Tile, TileBox};
 
 pub type ActionResult = Result<(), String>;
 
@@ -27,8 +34,15 @@ pub struct Engine {
 }
 
 impl Engine {
-    pub fn new(player_names: Vec<String>, cpu_player_count: u8) -> Result<Self, String> {
-        let nile = Nile::new(player_names, cpu_player_count)?;
+    /// Passing a `seed` makes the game reproducible: the same seed and the same actions always
+    /// result in the same game. Without a seed, one is chosen at random
+    pub fn new(
+        players: Vec<PlayerSetup>,
+        layout: BoardLayout,
+        rules: RuleSet,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        let nile = Nile::new(players, layout, rules, seed)?;
         let mut engine = Self {
             nile: Rc::new(nile),
             selected_tile: None,
@@ -40,6 +54,22 @@ impl Engine {
         Ok(engine)
     }
 
+    /// Restores a game from the output of `Engine::save`
+    pub fn load(save: &str) -> Result<Self, String> {
+        let (nile, log) = save::load(save)?;
+        Ok(Self {
+            nile: Rc::new(nile),
+            selected_tile: None,
+            log,
+            error_cells: None,
+        })
+    }
+
+    /// Serializes the complete game state, including the undo and redo history
+    pub fn save(&self) -> Result<String, String> {
+        save::save(&self.nile, &self.log)
+    }
+
     pub fn board(&self) -> &Board {
         self.nile.board()
     }
@@ -60,6 +90,38 @@ impl Engine {
         self.nile.current_player()
     }
 
+    pub fn seed(&self) -> u64 {
+        self.nile.seed()
+    }
+
+    /// Record of the game so far for replaying it
+    pub fn record(&self) -> GameRecord {
+        self.nile.record()
+    }
+
+    /// The best turns for the current player ranked by the default CPU strategy under the game's
+    /// rules. Tiles placed earlier in the turn are treated as if they were still in the player's
+    /// rack
+    pub fn hint(&self) -> Vec<Hint> {
+        let (board, rack) = self.nile.board_and_rack_before_turn();
+        self.nile
+            .default_strategy()
+            .take_turn(
+                &rack,
+                &board,
+                &self.nile.rules,
+                self.current_player().total_score(),
+                self.nile.other_player_scores(),
+            )
+            .into_iter()
+            .take(HINT_COUNT)
+            .map(|placements| Hint {
+                score: turn_score_under(&board, &rack, &placements, &self.nile.rules),
+                placements,
+            })
+            .collect()
+    }
+
     pub fn can_undo(&self) -> bool {
         self.log.can_undo()
     }
@@ -76,6 +138,10 @@ impl Engine {
         self.nile.has_ended()
     }
 
+    pub fn final_standings(&self) -> Option<FinalStandings> {
+        self.nile.final_standings()
+    }
+
     /// Returns the current `SelectedTile` if it exists, whether on the board or the rack
     pub fn selected_tile(&self) -> &Option<SelectedTile> {
         &self.selected_tile
@@ -281,7 +347,7 @@ impl Engine {
         }
     }
 
-    /// Process a CPU turn
+    /// Process a CPU turn. Returns whether a turn was taken, including “can’t play” turns
     fn take_cpu_turn(&mut self) -> bool {
         if self.nile.has_ended {
             return false;
@@ -290,50 +356,59 @@ impl Engine {
         if !player.is_cpu() {
             return false;
         }
-        // Hard-code CPU implementation for now
-        let lists_of_moves = Brute::new(self.nile.players.len()).take_turn(
-            player.tiles(),
-            &self.nile.board,
-            player.total_score(),
-            self.other_player_scores(),
-        );
-        let success = lists_of_moves.iter().any(|tile_placement_events| {
-            let success = tile_placement_events.iter().all(|tpe| {
-                if let Err(err) =
-                    self.mut_nile()
-                        .place_tile(tpe.tile_path_type, tpe.coordinates, tpe.rotation)
-                {
-                    crate::console::warn(&format!(
-                        "Failed to place a tile from CPU player: {:?}; TilePlacement: {:?}",
-                        err, &tpe
-                    ));
-                    self.undo_all();
-                    return false;
-                }
-                // Add to log in case there's a problem with the moves and everything needs to be
-                // undo
-                self.log
-                    .place_tile(tpe.tile_path_type, tpe.coordinates, tpe.rotation);
-                true
-            });
-            if success {
-                self.end_turn().map(|_| true).unwrap_or_else(|e| {
-                    crate::console::warn(&format!(
-                        "Failed to end CPU player turn: {:?}; Placements: {:?}",
-                        e, tile_placement_events,
-                    ));
-                    self.undo_all();
-                    false
-                })
-            } else {
-                false
+        let lists_of_moves = self.mut_nile().cpu_turns();
+        let success = lists_of_moves
+            .iter()
+            .any(|tile_placement_events| self.try_cpu_turn(tile_placement_events))
+            || self
+                .nile
+                .first_legal_turn()
+                .is_some_and(|placements| self.try_cpu_turn(&placements));
+        if !success {
+            // No legal moves, or all of them were invalid
+            if let Err(e) = self.mut_nile().cant_play() {
+                crate::console::warn(&format!("CPU player couldn’t play or pass: {:?}", e));
+                return false;
+            }
+            self.log.cant_play();
+            self.selected_tile = None;
+            self.error_cells = None;
+        }
+        true
+    }
+
+    /// Places and ends a turn from a CPU player, undoing it if any part is invalid
+    fn try_cpu_turn(&mut self, tile_placement_events: &[TilePlacementEvent]) -> bool {
+        let success = tile_placement_events.iter().all(|tpe| {
+            if let Err(err) =
+                self.mut_nile()
+                    .place_tile(tpe.tile_path_type, tpe.coordinates, tpe.rotation)
+            {
+                crate::console::warn(&format!(
+                    "Failed to place a tile from CPU player: {:?}; TilePlacement: {:?}",
+                    err, &tpe
+                ));
+                self.undo_all();
+                return false;
             }
+            // Add to log in case there's a problem with the moves and everything needs to be
+            // undo
+            self.log
+                .place_tile(tpe.tile_path_type, tpe.coordinates, tpe.rotation);
+            true
         });
-        if !success {
-            // Either no moves to begin with or all returned moves were invalid
-            let _end_turn_update = self.cant_play().unwrap();
+        if success {
+            self.end_turn().map(|_| true).unwrap_or_else(|e| {
+                crate::console::warn(&format!(
+                    "Failed to end CPU player turn: {:?}; Placements: {:?}",
+                    e, tile_placement_events,
+                ));
+                self.undo_all();
+                false
+            })
+        } else {
+            false
         }
-        success
     }
 
     fn undo_all(&mut self) {
@@ -381,26 +456,10 @@ impl Engine {
         };
         Ok(())
     }
-
-    /// Get scores of players other than the current player
-    fn other_player_scores(&self) -> Vec<i16> {
-        self.nile
-            .players()
-            .iter()
-            .enumerate()
-            .filter_map(|(id, player)| {
-                if id == self.nile.current_turn() {
-                    None
-                } else {
-                    Some(player.total_score())
-                }
-            })
-            .collect()
-    }
 }
 
 /// Holds all game state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Nile {
     // the game board
     board: Rc<Board>,
@@ -417,8 +476,65 @@ pub struct Nile {
     cant_play_count: u8,
     /// Whether the game has ended
     has_ended: bool,
+    /// Seed for all of the game's randomness
+    seed: u64,
+    /// Strategy of each CPU player, indexed like `players`. Saved as their `StrategyState`
+    #[serde(with = "saved_strategies")]
+    strategies: Vec<Option<Box<dyn CPUPlayer>>>,
+    /// Committed turns of all players in order
+    turns: Vec<TurnRecord>,
+    /// Layout the board was created from. Saves from before layouts existed use the standard one
+    #[serde(default)]
+    layout: BoardLayout,
+    rules: RuleSet,
+}
+
+/// Saves CPU strategies as their `StrategyState` and rebuilds them on load
+mod saved_strategies {
+    use crate::ai::{Brute, CPUPlayer, StrategyState};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        strategies: &[Option<Box<dyn CPUPlayer>>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let states: Vec<Option<StrategyState>> = strategies
+            .iter()
+            .map(|strategy| strategy.as_ref().and_then(|strategy| strategy.state()))
+            .collect();
+        states.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Option<Box<dyn CPUPlayer>>>, D::Error> {
+        let states = Vec::<Option<StrategyState>>::deserialize(deserializer)?;
+        // Strategies are indexed like the players
+        let player_count = states.len();
+        Ok(states
+            .into_iter()
+            .map(|state| {
+                state.map(|state| {
+                    Box::new(Brute::from_state(player_count, state)) as Box<dyn CPUPlayer>
+                })
+            })
+            .collect())
+    }
 }
 
+/// A suggested turn from `Engine::hint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    /// Placements in the order they extend the river
+    pub placements: Vec<TilePlacementEvent>,
+    /// Score the turn would earn, excluding any penalties for the next player
+    pub score: TurnScore,
+}
+
+/// Maximum number of turns returned by `Engine::hint`
+const HINT_COUNT: usize = 5;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SelectedTile {
     /// index of selected tile in rack
@@ -427,29 +543,49 @@ pub enum SelectedTile {
 }
 
 impl Nile {
-    pub fn new(player_names: Vec<String>, cpu_player_count: u8) -> Result<Self, String> {
-        let player_count = player_names.len() + cpu_player_count as usize;
-        if !(2..=4).contains(&player_count) {
-            Err("Nile is a game for 2-4 players".to_owned())
+    pub fn new(
+        players: Vec<PlayerSetup>,
+        layout: BoardLayout,
+        rules: RuleSet,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        rules.validate()?;
+        let player_count = players.len();
+        if !rules.player_counts().contains(&player_count) {
+            Err(format!(
+                "Nile is a game for {}-{} players",
+                rules.min_players, rules.max_players
+            ))
         } else {
-            let mut tile_box = TileBox::default();
-            let mut players: Vec<Player> = player_names
+            let seed = seed.unwrap_or_else(random_seed);
+            let mut rng = GameRng::new(seed);
+            let first_turn = rng.gen_range(0, player_count);
+            let mut tile_box = TileBox::with_rng(rng);
+            let (players, strategies) = players
                 .into_iter()
-                .map(|player| Player::new(player, &mut tile_box, false))
-                .collect();
-            for i in 1..=cpu_player_count {
-                players.push(Player::new(format!("cpu{}", i), &mut tile_box, true))
-            }
-            let mut rng = rand::thread_rng();
-            let first_turn = rng.gen_range(0, players.len());
+                .map(|setup| match setup {
+                    PlayerSetup::Human { name } => {
+                        (Player::new(name, &mut tile_box, false, &rules), None)
+                    }
+                    PlayerSetup::Cpu { name, strategy } => (
+                        Player::new(name, &mut tile_box, true, &rules),
+                        Some(strategy),
+                    ),
+                })
+                .unzip();
             Ok(Self {
-                board: Rc::new(Board::new()),
+                board: Rc::new(Board::from_layout(&layout)?),
                 tile_box,
                 players,
                 current_turn: first_turn,
                 current_turn_placements: HashSet::default(),
                 cant_play_count: 0,
                 has_ended: false,
+                seed,
+                strategies,
+                turns: Vec::new(),
+                layout,
+                rules,
             })
         }
     }
@@ -482,10 +618,145 @@ impl Nile {
         &self.current_turn_placements
     }
 
+    /// Get scores of players other than the current player, in turn order starting with the next
+    /// player
+    pub(crate) fn other_player_scores(&self) -> Vec<i16> {
+        let player_count = self.players.len();
+        (1..player_count)
+            .map(|i| self.players[(self.current_turn + i) % player_count].total_score())
+            .collect()
+    }
+
+    /// Ranked turns from the current player's CPU strategy. Empty for human players
+    pub(crate) fn cpu_turns(&mut self) -> Vec<Vec<TilePlacementEvent>> {
+        let other_scores = self.other_player_scores();
+        let player = &self.players[self.current_turn];
+        match self
+            .strategies
+            .get_mut(self.current_turn)
+            .and_then(Option::as_mut)
+        {
+            Some(strategy) => strategy.take_turn(
+                player.tiles(),
+                &self.board,
+                &self.rules,
+                player.total_score(),
+                other_scores,
+            ),
+            None => Vec::new(),
+        }
+    }
+
+    /// Strategy of CPU players that weren't given one, which also ranks hints
+    pub(crate) fn default_strategy(&self) -> Brute {
+        Brute::new(self.players.len())
+    }
+
+    /// Gives CPU players without a strategy, e.g. loaded ones whose strategy had no
+    /// `StrategyState`, the default strategy
+    pub(crate) fn restore_strategies(&mut self) {
+        let default_strategy = self.default_strategy();
+        self.strategies.resize(self.players.len(), None);
+        for (player, strategy) in self.players.iter().zip(self.strategies.iter_mut()) {
+            if player.is_cpu() && strategy.is_none() {
+                *strategy = Some(Box::new(default_strategy.clone()));
+            }
+        }
+    }
+
+    /// The board and current player's rack as they were at the beginning of the turn
+    fn board_and_rack_before_turn(&self) -> (Board, TileArray) {
+        let mut board = self.board().clone();
+        let mut rack = self.current_player().tiles().clone();
+        let mut coordinates: Vec<_> = self.current_turn_placements.iter().collect();
+        // Consistent rack order regardless of `HashSet` order
+        coordinates.sort_by_key(|c| (c.0, c.1));
+        for c in coordinates {
+            if let Some((tile_placement, _)) = board.remove_tile(*c) {
+                rack.push(Tile::from(tile_placement.tile_path_type()));
+            }
+        }
+        (board, rack)
+    }
+
     pub fn has_ended(&self) -> bool {
         self.has_ended
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Committed turns of all players in order
+    pub fn turns(&self) -> &[TurnRecord] {
+        &self.turns
+    }
+
+    /// Record of the game so far for replaying it
+    pub fn record(&self) -> GameRecord {
+        GameRecord {
+            seed: self.seed,
+            players: self
+                .players
+                .iter()
+                .map(|p| RecordedPlayer {
+                    name: p.name().to_owned(),
+                    is_cpu: p.is_cpu(),
+                })
+                .collect(),
+            layout: self.layout.clone(),
+            rules: self.rules.clone(),
+            turns: self.turns.clone(),
+        }
+    }
+
+    /// Plays a complete turn for the current player. If any part of the turn is invalid, the
+    /// turn is left partially played
+    pub fn play_turn(&mut self, turn: &TurnRecord) -> error::Result<bool> {
+        match turn {
+            TurnRecord::Placements(placements) => {
+                for tpe in placements {
+                    self.place_tile(tpe.tile_path_type, tpe.coordinates, tpe.rotation)?;
+                }
+                self.end_turn()
+            }
+            TurnRecord::CantPlay => self.cant_play(),
+        }
+    }
+
+    /// Plays the current player's turn with their CPU strategy: the first of the strategy's
+    /// turns that's valid, else the first legal turn if the `CantPlayCheck` requires one, else
+    /// “can’t play”. Returns whether the game ended
+    pub fn play_cpu_turn(&mut self) -> error::Result<bool> {
+        self.if_not_ended()?;
+        if !self.current_player().is_cpu() {
+            return Err(Error::Msg(format!(
+                "{} isn’t a CPU player",
+                self.current_player().name()
+            )));
+        }
+        for placements in self.cpu_turns() {
+            if let Some(has_ended) = self.try_turn(placements) {
+                return Ok(has_ended);
+            }
+        }
+        if let Some(placements) = self.first_legal_turn() {
+            if let Some(has_ended) = self.try_turn(placements) {
+                return Ok(has_ended);
+            }
+        }
+        self.cant_play()
+    }
+
+    /// Plays a turn on a copy so an invalid turn doesn't leave the game partially played.
+    /// Returns whether the game ended, or `None` if the turn was invalid
+    fn try_turn(&mut self, placements: Vec<TilePlacementEvent>) -> Option<bool> {
+        let mut nile = self.clone();
+        let has_ended = nile.play_turn(&TurnRecord::Placements(placements)).ok()?;
+        *self = nile;
+        Some(has_ended)
+    }
+
     pub fn place_tile(
         &mut self,
         tile_path_type: TilePathType,
@@ -498,8 +769,16 @@ impl Nile {
         player
             .place_tile(tile)
             .ok_or_else(|| Error::Msg(format!("Player doesn’t have a {:?}", tile)))?;
+        let placed_by = PlacedBy {
+            player: self.current_turn,
+            turn: self.turns.len(),
+        };
         let event_score = Rc::make_mut(&mut self.board)
-            .place_tile(coordinates, TilePlacement::new(tile_path_type, rotation))
+            .place_tile_by(
+                coordinates,
+                TilePlacement::new(tile_path_type, rotation),
+                placed_by,
+            )
             .map_err(|e| {
                 // Player's tile rack should be unchanged
                 player.return_tile(tile);
@@ -595,15 +874,57 @@ impl Nile {
         if !self.current_turn_placements.is_empty() {
             return Err(Error::Msg("Player has placed tiles this turn".to_owned()));
         }
+        self.check_cant_play()?;
+        self.pass_turn();
+        Ok(self.has_ended)
+    }
+
+    /// Verifies the current player has no legal moves according to the `CantPlayCheck` rule
+    fn check_cant_play(&self) -> error::Result<()> {
+        match self.first_legal_turn() {
+            None => Ok(()),
+            Some(_) if self.rules.cant_play_check == CantPlayCheck::Reject => Err(Error::Msg(
+                "Can’t claim “can’t play” when there’s a legal move".to_owned(),
+            )),
+            Some(placements) => Err(Error::cells(
+                placements.iter().map(|p| p.coordinates).collect(),
+                format!(
+                    "Can’t claim “can’t play” when there’s a legal move, e.g. {}",
+                    TurnRecord::Placements(placements)
+                ),
+            )),
+        }
+    }
+
+    /// The first legal turn for the current player, or `None` if there isn't one or the
+    /// `CantPlayCheck` is off so they're allowed to pass anyway
+    fn first_legal_turn(&self) -> Option<Vec<TilePlacementEvent>> {
+        if self.rules.cant_play_check == CantPlayCheck::Off {
+            return None;
+        }
+        legal_turns_under(
+            &self.board,
+            self.current_player().tiles(),
+            self.rules.river_check,
+        )
+        .next()
+    }
+
+    /// Ends the current player's turn by discarding their tiles without checking whether they
+    /// could've played
+    fn pass_turn(&mut self) {
         let player_count = self.players.len();
         let player = self.players.get_mut(self.current_turn).expect("Player");
-        // TODO: Check if any playable moves
-        let _turn_score = player.cant_play(&mut self.tile_box);
+        let _turn_score = player.cant_play(&mut self.tile_box, &self.rules);
+        self.turns.push(TurnRecord::CantPlay);
 
         self.cant_play_count += 1;
         self.has_ended = self.cant_play_count as usize == player_count;
+        // No more tiles are drawn once the game has ended
+        if !self.has_ended {
+            player.draw_tiles(&mut self.tile_box, &self.rules);
+        }
         self.advance_turn();
-        Ok(self.has_ended)
     }
 
     /// Called when a human player ends their turn normally (they played at least one tile)
@@ -612,10 +933,18 @@ impl Nile {
         if self.current_turn_placements.is_empty() {
             return Err(Error::Msg("Can’t end turn normally without placing at least one tile. Use “can’t play” if there are no playable moves".to_owned()));
         }
+        let last_placement = self.board.last_placement();
         self.has_ended = Rc::make_mut(&mut self.board)
-            .validate_turns_moves(self.current_turn_placements.clone())?;
+            .validate_turns_moves(self.current_turn_placements.clone(), self.rules.river_check)?;
+        let placements = self.current_turn_events(last_placement);
+        self.turns.push(TurnRecord::Placements(placements));
+        let played = self.current_turn_placements.len();
         let player = self.players.get_mut(self.current_turn).expect("Player");
-        let _turn_score = player.end_turn(&mut self.tile_box);
+        let _turn_score = player.end_turn(&self.rules, played);
+        // No more tiles are drawn once the game has ended
+        if !self.has_ended {
+            player.draw_tiles(&mut self.tile_box, &self.rules);
+        }
         self.advance_turn();
         // Reset count
         self.cant_play_count = 0;
@@ -623,10 +952,118 @@ impl Nile {
         Ok(self.has_ended)
     }
 
+    /// The current turn's placements in the order they extend the river from `last_placement`.
+    /// Should only be called once the turn has been validated
+    fn current_turn_events(
+        &self,
+        mut last_placement: (Coordinates, Offset),
+    ) -> Vec<TilePlacementEvent> {
+        let mut events = Vec::with_capacity(self.current_turn_placements.len());
+        while events.len() < self.current_turn_placements.len() {
+            let coordinates = last_placement.0 + last_placement.1;
+            let tile = self
+                .board
+                .cell(coordinates)
+                .and_then(|cell| cell.tile())
+                .expect("validated tile");
+            let event = TilePlacementEvent {
+                tile_path_type: *tile.tile_path_type(),
+                coordinates,
+                rotation: tile.rotation(),
+            };
+            last_placement = eval_placement(last_placement, &event).expect("validated placement");
+            events.push(event);
+        }
+        events
+    }
+
+    /// Last step of every turn
     fn advance_turn(&mut self) {
         self.current_turn = (self.current_turn + 1) % self.players.len();
-        self.has_ended = self.has_ended || self.players[self.current_turn].rack_is_empty();
+        self.has_ended = self.has_ended
+            || (self.rules.empty_rack_ends_game && self.players[self.current_turn].rack_is_empty());
         self.current_turn_placements.clear();
+        if self.has_ended {
+            self.final_scoring();
+        }
+    }
+
+    /// Settles what's left in the players' racks once the game has ended
+    fn final_scoring(&mut self) {
+        if self.rules.rack_penalty_at_end {
+            for player in self.players.iter_mut() {
+                player.settle_rack();
+            }
+        }
+    }
+
+    /// Ranks of the players after final scoring. `None` until the game has ended
+    pub fn final_standings(&self) -> Option<FinalStandings> {
+        self.has_ended.then(|| {
+            let scores: Vec<i16> = self.players.iter().map(|p| p.total_score()).collect();
+            FinalStandings::new(&scores)
+        })
+    }
+
+    /// Checks the consistency of a `Nile` that wasn't created by `Nile::new`, e.g. one that was
+    /// loaded from a save
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        self.board.validate_dimensions()?;
+        self.rules.validate()?;
+        if !self.rules.player_counts().contains(&self.players.len()) {
+            return Err(format!(
+                "Nile is a game for {}-{} players, found {}",
+                self.rules.min_players,
+                self.rules.max_players,
+                self.players.len()
+            ));
+        }
+        if self.current_turn >= self.players.len() {
+            return Err(format!(
+                "Current turn {} is not a valid player index",
+                self.current_turn
+            ));
+        }
+        if let Some(player) = self
+            .players
+            .iter()
+            .find(|p| p.tiles().len() > self.rules.rack_size)
+        {
+            return Err(format!(
+                "{} has more than {} tiles",
+                player.name(),
+                self.rules.rack_size
+            ));
+        }
+        if let Some(coordinates) = self
+            .current_turn_placements
+            .iter()
+            .find(|c| !self.board.has_tile(**c))
+        {
+            return Err(format!(
+                "Missing tile from the current turn at {}",
+                coordinates
+            ));
+        }
+        // Every tile must be in exactly one place: the box, a rack, or the board
+        let mut tile_counts = [0i16; 9];
+        TileBox::all_tiles()
+            .iter()
+            .for_each(|t| tile_counts[*t as usize] += 1);
+        self.tile_box
+            .tiles()
+            .chain(self.players.iter().flat_map(|p| p.tiles().iter()))
+            .copied()
+            .chain(
+                self.board
+                    .tiles()
+                    .map(|(_, tp)| Tile::from(tp.tile_path_type())),
+            )
+            .for_each(|t| tile_counts[t as usize] -= 1);
+        if tile_counts.iter().any(|count| *count != 0) {
+            return Err("Tiles don’t match the tiles of a complete game".to_owned());
+        }
+        Ok(())
     }
 
     fn if_not_ended(&self) -> error::Result<()> {
@@ -641,9 +1078,24 @@ impl Nile {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ai::Difficulty;
+    use crate::analysis::GameAnalysis;
+    use crate::board::BOARD_DIM;
+    use crate::player::TileArray;
+    use crate::replay::Replay;
+    use crate::rules::RiverCheck;
+    use crate::score::ScoreBreakdown;
+
+    use smallvec::smallvec;
 
     fn setup() -> Nile {
-        let mut nile = Nile::new(vec!["player1".to_owned(), "player2".to_owned()], 0).unwrap();
+        let mut nile = Nile::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet::default(),
+            None,
+        )
+        .unwrap();
         nile.current_turn = 0;
         nile
     }
@@ -653,8 +1105,8 @@ mod test {
             .tiles()
             .iter()
             .find(|t| **t != Tile::Universal)
+            .copied()
             .expect("Non-universal tile (there's max four)")
-            .clone()
     }
 
     /// Placing and removing a tile should have no net effect on the score
@@ -679,18 +1131,548 @@ mod test {
     fn move_tile_has_no_score_change_except_for_bonues() {
         let mut target = setup();
         let tile = get_normal_tile(&mut target);
-        let begin_score = target
+        target
             .place_tile(
                 TilePathType::from(tile),
                 Coordinates(10, 0),
                 Rotation::Clockwise90,
             )
             .unwrap();
-        let end_score = target
-            // Neither cell has a bonus
+        let begin_score = target.current_player().current_turn_score();
+        // Neither cell has a bonus
+        target
             .move_tile(Coordinates(10, 0), Coordinates(9, 0))
             .unwrap();
-        assert_eq!(begin_score, end_score);
+        assert_eq!(begin_score, target.current_player().current_turn_score());
+    }
+
+    #[test]
+    fn placed_by_follows_tile() {
+        let mut target = Engine::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(3),
+        )
+        .unwrap();
+        target.cant_play().unwrap();
+        let placed_by = Some(PlacedBy {
+            player: target.current_turn(),
+            turn: 1,
+        });
+        let placed_by_at = |target: &Engine, c| target.board().cell(c).unwrap().placed_by();
+        target.select_rack_tile(0).unwrap();
+        target.place_tile(Coordinates(10, 0)).unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(10, 0)), placed_by);
+        target.rotate_selected_tile(Rotation::Clockwise90).unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(10, 0)), placed_by);
+        target.place_tile(Coordinates(9, 0)).unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(10, 0)), None);
+        assert_eq!(placed_by_at(&target, Coordinates(9, 0)), placed_by);
+        target.remove_selected_tile().unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(9, 0)), None);
+        target.undo().unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(9, 0)), placed_by);
+        target.undo().unwrap();
+        assert_eq!(placed_by_at(&target, Coordinates(10, 0)), placed_by);
+        assert_eq!(placed_by_at(&target, Coordinates(9, 0)), None);
+    }
+
+    #[test]
+    fn breakdown_survives_undo_redo() {
+        let mut target = Engine::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(3),
+        )
+        .unwrap();
+        let tile = target.current_player().tiles()[0];
+        target.select_rack_tile(0).unwrap();
+        target.place_tile(Coordinates(10, 0)).unwrap();
+        let placed = target.current_player().current_turn_breakdown();
+        assert_eq!(placed.tiles, tile.score());
+        assert_eq!(
+            placed.turn_score(),
+            target.current_player().current_turn_score()
+        );
+        target.undo().unwrap();
+        assert_eq!(
+            target.current_player().current_turn_breakdown(),
+            ScoreBreakdown::default()
+        );
+        target.redo().unwrap();
+        assert_eq!(target.current_player().current_turn_breakdown(), placed);
+    }
+
+    #[test]
+    fn cant_play_with_legal_move() {
+        let mut target = setup();
+        target.players[0].set_tiles(smallvec![Tile::Diagonal, Tile::Straight]);
+        let res = target.cant_play();
+        assert!(matches!(res, Err(Error::Msg(msg)) if msg.contains("legal move")));
+        assert_eq!(target.current_turn(), 0);
+    }
+
+    #[test]
+    fn cant_play_shows_legal_move() {
+        let mut target = setup();
+        target.rules.cant_play_check = CantPlayCheck::RejectWithMove;
+        target.players[0].set_tiles(smallvec![Tile::Diagonal, Tile::Straight]);
+        let res = target.cant_play();
+        assert!(matches!(
+            res,
+            Err(Error::Cell(e)) if e.msg.ends_with("Str@10,0/R0 E")
+                && e.coordinates.contains(&Coordinates(10, 0))
+        ));
+    }
+
+    #[test]
+    fn cant_play_without_legal_move() {
+        let mut target = setup();
+        // None of these can connect to the start arrow
+        target.players[0].set_tiles(smallvec![Tile::Diagonal, Tile::Corner90, Tile::Diagonal]);
+        assert!(target.cant_play().is_ok());
+        assert_eq!(target.current_turn(), 1);
+    }
+
+    #[test]
+    fn cant_play_check_off() {
+        let mut target = setup();
+        target.rules.cant_play_check = CantPlayCheck::Off;
+        target.players[0].set_tiles(smallvec![Tile::Straight]);
+        assert!(target.cant_play().is_ok());
+    }
+
+    #[test]
+    fn hint_ignores_current_turn_placements() {
+        let mut target = Engine::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(3),
+        )
+        .unwrap();
+        let hints = target.hint();
+        assert!(!hints.is_empty() && hints.len() <= HINT_COUNT);
+        let first = hints[0].placements[0].clone();
+        let tile = Tile::from(&first.tile_path_type);
+        let rack_idx = target
+            .current_player()
+            .tiles()
+            .iter()
+            .position(|t| *t == tile)
+            .unwrap();
+        target.select_rack_tile(rack_idx as u8).unwrap();
+        target.place_tile(first.coordinates).unwrap();
+        if let TilePathType::Universal(tile_path) = first.tile_path_type {
+            target.update_selected_universal_path(tile_path).unwrap();
+        }
+        target.rotate_selected_tile(first.rotation).unwrap();
+        assert_eq!(target.hint(), hints);
+    }
+
+    /// Records how many turns it had taken each time it's asked for a turn
+    #[derive(Clone, Debug)]
+    struct CountingCpu {
+        brute: Brute,
+        turns_taken: usize,
+        history: Rc<std::cell::RefCell<Vec<usize>>>,
+    }
+
+    impl CPUPlayer for CountingCpu {
+        fn take_turn(
+            &mut self,
+            tiles: &TileArray,
+            board: &Board,
+            rules: &RuleSet,
+            score: i16,
+            other_scores: Vec<i16>,
+        ) -> Vec<Vec<TilePlacementEvent>> {
+            self.history.borrow_mut().push(self.turns_taken);
+            self.turns_taken += 1;
+            self.brute
+                .take_turn(tiles, board, rules, score, other_scores)
+        }
+    }
+
+    #[test]
+    fn other_player_scores_in_turn_order() {
+        let mut target = Nile::new(
+            PlayerSetup::humans_and_cpus(
+                vec![
+                    "player1".to_owned(),
+                    "player2".to_owned(),
+                    "player3".to_owned(),
+                ],
+                0,
+            ),
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(3),
+        )
+        .unwrap();
+        target.current_turn = 1;
+        target.cant_play().unwrap();
+        target.cant_play().unwrap();
+        assert_eq!(target.current_turn, 0);
+        let scores: Vec<i16> = target.players.iter().map(|p| p.total_score()).collect();
+        assert_ne!(scores[1], scores[2]);
+        assert_eq!(target.other_player_scores(), [scores[1], scores[2]]);
+        target.current_turn = 1;
+        assert_eq!(target.other_player_scores(), [scores[2], scores[0]]);
+    }
+
+    #[test]
+    fn cpu_strategies_keep_state() {
+        let history = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let target = Engine::new(
+            vec![
+                PlayerSetup::cpu(
+                    "counting".to_owned(),
+                    Box::new(CountingCpu {
+                        brute: Brute::new(2),
+                        turns_taken: 0,
+                        history: history.clone(),
+                    }),
+                ),
+                PlayerSetup::cpu("brute".to_owned(), Box::new(Brute::new(2))),
+            ],
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(99),
+        )
+        .unwrap();
+        assert!(target.has_ended());
+        let history = history.borrow();
+        assert!(!history.is_empty());
+        assert!(history.iter().enumerate().all(|(i, turns)| i == *turns));
+        assert_eq!(target.players()[0].name(), "counting");
+    }
+
+    /// Never finds a move
+    #[derive(Clone, Debug)]
+    struct PassingCpu;
+
+    impl CPUPlayer for PassingCpu {
+        fn take_turn(
+            &mut self,
+            _tiles: &TileArray,
+            _board: &Board,
+            _rules: &RuleSet,
+            _score: i16,
+            _other_scores: Vec<i16>,
+        ) -> Vec<Vec<TilePlacementEvent>> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn cpu_pass_replays() {
+        let target = Engine::new(
+            vec![
+                PlayerSetup::cpu("passing1".to_owned(), Box::new(PassingCpu)),
+                PlayerSetup::cpu("passing2".to_owned(), Box::new(PassingCpu)),
+            ],
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(5),
+        )
+        .unwrap();
+        assert!(target.has_ended());
+        let record = target.record();
+        assert_eq!(record.turns, [TurnRecord::CantPlay, TurnRecord::CantPlay]);
+        let mut replay = Replay::new(record.clone()).unwrap();
+        assert!(replay.seek(2));
+        assert_eq!(replay.nile().players(), target.players());
+        let analysis = GameAnalysis::new(&record).unwrap();
+        assert!(analysis.turns().iter().all(|t| t.missed() > 0));
+    }
+
+    #[test]
+    fn cpu_without_turns_plays_a_legal_one() {
+        let mut target = Nile::new(
+            vec![
+                PlayerSetup::cpu("passing1".to_owned(), Box::new(PassingCpu)),
+                PlayerSetup::cpu("passing2".to_owned(), Box::new(PassingCpu)),
+            ],
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(5),
+        )
+        .unwrap();
+        let expected = target.first_legal_turn().unwrap();
+        target.play_cpu_turn().unwrap();
+        assert_eq!(target.turns, [TurnRecord::Placements(expected)]);
+
+        let target = Engine::new(
+            vec![
+                PlayerSetup::cpu("passing1".to_owned(), Box::new(PassingCpu)),
+                PlayerSetup::cpu("passing2".to_owned(), Box::new(PassingCpu)),
+            ],
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(5),
+        )
+        .unwrap();
+        assert!(matches!(
+            target.record().turns[0],
+            TurnRecord::Placements(_)
+        ));
+    }
+
+    #[test]
+    fn final_scoring_subtracts_racks() {
+        let target = Engine::new(
+            vec![
+                PlayerSetup::cpu("passing1".to_owned(), Box::new(PassingCpu)),
+                PlayerSetup::cpu("passing2".to_owned(), Box::new(PassingCpu)),
+            ],
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                rack_penalty_at_end: true,
+                ..RuleSet::default()
+            },
+            Some(5),
+        )
+        .unwrap();
+        assert!(target.has_ended());
+        // The game-ending pass discards the last rack without drawing a new one to penalize
+        assert!(target.players()[1].rack_is_empty());
+        for player in target.players() {
+            let rack_value: i16 = player.tiles().iter().map(|t| t.score()).sum();
+            assert_eq!(player.scores().len(), 2);
+            assert_eq!(
+                player.scores()[1],
+                TurnScore {
+                    add: 0,
+                    sub: rack_value
+                }
+            );
+        }
+        let standings = target.final_standings().unwrap();
+        assert_eq!(standings.standings().len(), 2);
+        let winner = &target.players()[standings.winners()[0]];
+        assert!(target
+            .players()
+            .iter()
+            .all(|p| p.total_score() <= winner.total_score()));
+    }
+
+    #[test]
+    fn no_standings_before_end() {
+        let target = setup();
+        assert!(target.final_standings().is_none());
+    }
+
+    #[test]
+    fn same_seed_same_setup() {
+        let player_names = vec!["player1".to_owned(), "player2".to_owned()];
+        let target1 = Nile::new(
+            PlayerSetup::humans_and_cpus(player_names.clone(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(42),
+        )
+        .unwrap();
+        let target2 = Nile::new(
+            PlayerSetup::humans_and_cpus(player_names, 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(42),
+        )
+        .unwrap();
+        assert_eq!(target1.seed(), 42);
+        assert_eq!(target1.current_turn(), target2.current_turn());
+        assert_eq!(target1.players(), target2.players());
+    }
+
+    #[test]
+    fn same_seed_same_cpu_game() {
+        let target1 = Engine::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(1234),
+        )
+        .unwrap();
+        let target2 = Engine::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(1234),
+        )
+        .unwrap();
+        // Only CPU players so the whole game is played in the constructor
+        assert!(target1.has_ended());
+        assert_eq!(target1.players(), target2.players());
+        assert_eq!(target1.current_turn(), target2.current_turn());
+    }
+
+    #[test]
+    fn cpu_plays_after_cpu_cant_play() {
+        let mut nile = Nile::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(5),
+        )
+        .unwrap();
+        // The first CPU has nothing to play
+        let first_cpu = nile.current_turn;
+        nile.players[first_cpu].set_tiles(TileArray::new());
+        let mut target = Engine {
+            nile: Rc::new(nile),
+            selected_tile: None,
+            log: Log::new(),
+            error_cells: None,
+        };
+        target.take_cpu_turns_if_any();
+        assert_eq!(target.nile.turns()[0], TurnRecord::CantPlay);
+        assert!(matches!(target.nile.turns()[1], TurnRecord::Placements(_)));
+    }
+
+    #[test]
+    fn hint_agrees_with_cant_play_check() {
+        // Continuing up column 2 leads into a pocket walled off by row 1 and column 5
+        let mut board = Board::with_last_placement(Coordinates(4, 2), Offset(-1, 0));
+        let straight =
+            |rotation| TilePlacement::new(TilePathType::Normal(TilePath::Straight), rotation);
+        for row in 0..BOARD_DIM as i8 {
+            board
+                .place_tile(Coordinates(row, 5), straight(Rotation::Clockwise90))
+                .unwrap();
+        }
+        for column in 0..5 {
+            board
+                .place_tile(Coordinates(1, column), straight(Rotation::None))
+                .unwrap();
+        }
+        for river_check in [RiverCheck::Escape, RiverCheck::Reachable] {
+            let mut nile = Nile::new(
+                PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+                BoardLayout::default(),
+                RuleSet {
+                    river_check,
+                    ..RuleSet::default()
+                },
+                Some(5),
+            )
+            .unwrap();
+            nile.board = Rc::new(board.clone());
+            let current_turn = nile.current_turn;
+            nile.players[current_turn].set_tiles(smallvec![Tile::Straight]);
+            let target = Engine {
+                nile: Rc::new(nile.clone()),
+                selected_tile: None,
+                log: Log::new(),
+                error_cells: None,
+            };
+            assert_eq!(target.hint().is_empty(), nile.cant_play().is_ok());
+            assert_eq!(
+                target.hint().is_empty(),
+                river_check == RiverCheck::Reachable
+            );
+        }
+    }
+
+    #[test]
+    fn nile_plays_cpu_game() {
+        let mut target = Nile::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(1234),
+        )
+        .unwrap();
+        while !target.has_ended() {
+            target.play_cpu_turn().unwrap();
+        }
+        let engine = Engine::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(1234),
+        )
+        .unwrap();
+        assert_eq!(target.players(), engine.players());
+        assert!(target.play_cpu_turn().is_err());
+    }
+
+    #[test]
+    fn seeded_games_with_cpu_difficulties_repeat() {
+        let game = || {
+            Engine::new(
+                PlayerSetup::humans_and_cpus_with_difficulties(
+                    Vec::new(),
+                    &[Difficulty::Easy, Difficulty::Medium],
+                    77,
+                ),
+                BoardLayout::default(),
+                RuleSet::default(),
+                Some(77),
+            )
+            .unwrap()
+        };
+        let target = game();
+        assert!(target.has_ended());
+        assert_eq!(target.record(), game().record());
+    }
+
+    #[test]
+    fn play_cpu_turn_for_human() {
+        let mut target = Nile::new(
+            PlayerSetup::humans_and_cpus(vec!["human1".to_owned(), "human2".to_owned()], 0),
+            BoardLayout::default(),
+            RuleSet::default(),
+            None,
+        )
+        .unwrap();
+        assert!(target.play_cpu_turn().is_err());
+        assert!(target.turns().is_empty());
+    }
+
+    #[test]
+    fn house_rules_player_counts_and_rack_size() {
+        let rules = RuleSet {
+            min_players: 3,
+            max_players: 6,
+            rack_size: 4,
+            ..RuleSet::default()
+        };
+        let two_players = PlayerSetup::humans_and_cpus(vec!["p1".to_owned(), "p2".to_owned()], 0);
+        assert!(Nile::new(two_players, BoardLayout::default(), rules.clone(), None).is_err());
+        let target = Nile::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 5),
+            BoardLayout::default(),
+            rules,
+            Some(7),
+        )
+        .unwrap();
+        assert!(target.players().iter().all(|p| p.tiles().len() == 4));
+        assert!(target.validate().is_ok());
+    }
+
+    #[test]
+    fn empty_rack_doesnt_end_game() {
+        let mut target = setup();
+        target.rules.empty_rack_ends_game = false;
+        target.players[1].set_tiles(TileArray::new());
+        target.advance_turn();
+        assert!(!target.has_ended);
+        // With nothing to play, the player can only claim they can't play
+        assert!(target.cant_play().is_ok());
+        assert_eq!(target.players[1].total_score(), 0);
     }
 
     #[test]
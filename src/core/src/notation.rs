@@ -0,0 +1,427 @@
+//! Compact text notation for turns, e.g. `Str@10,0/R0 Uni(L45)@10,1/R90 E`
+//!
+//! A turn is either `X` for “can’t play” or one or more tile placements separated by whitespace,
+//! optionally followed by `E` for “end turn”. Each placement is written as
+//! `<tile>@<row>,<column>/<rotation>` where:
+//! * `<tile>` is one of `Str`, `Diag`, `Ctr90`, `Cnr90`, `L45`, `R45`, `L135` or `R135`, or a
+//!   universal tile acting as one of them, e.g. `Uni(Diag)`
+//! * `<rotation>` is the clockwise rotation in degrees: `R0`, `R90`, `R180` or `R270`
+//!
+//! Tiles in a rack are written by their name alone, with `Uni` for the universal tile
+use crate::log::TilePlacementEvent;
+use crate::path::{TilePath, TilePathType};
+use crate::record::TurnRecord;
+use crate::tile::{Coordinates, Rotation, Tile};
+
+use std::fmt;
+use std::str::FromStr;
+
+const TILE_PATH_NAMES: [(TilePath, &str); 8] = [
+    (TilePath::Straight, "Str"),
+    (TilePath::Diagonal, "Diag"),
+    (TilePath::Center90, "Ctr90"),
+    (TilePath::Corner90, "Cnr90"),
+    (TilePath::Left45, "L45"),
+    (TilePath::Right45, "R45"),
+    (TilePath::Left135, "L135"),
+    (TilePath::Right135, "R135"),
+];
+
+const ROTATION_NAMES: [(Rotation, &str); 4] = [
+    (Rotation::None, "R0"),
+    (Rotation::Clockwise90, "R90"),
+    (Rotation::Clockwise180, "R180"),
+    (Rotation::Clockwise270, "R270"),
+];
+
+const UNIVERSAL: &str = "Uni";
+const END_TURN: &str = "E";
+const CANT_PLAY: &str = "X";
+
+/// Error from parsing notation. `position` is the character offset in the input where the error
+/// was found
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotationError {
+    pub position: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.msg, self.position)
+    }
+}
+
+fn tile_path_name(tile_path: TilePath) -> &'static str {
+    TILE_PATH_NAMES
+        .iter()
+        .find(|(tp, _)| *tp == tile_path)
+        .map(|(_, name)| *name)
+        .expect("all tile paths have a name")
+}
+
+fn rotation_name(rotation: Rotation) -> &'static str {
+    ROTATION_NAMES
+        .iter()
+        .find(|(r, _)| *r == rotation)
+        .map(|(_, name)| *name)
+        .expect("all rotations have a name")
+}
+
+impl fmt::Display for TilePlacementEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tile_path_type {
+            TilePathType::Normal(tp) => write!(f, "{}", tile_path_name(tp))?,
+            TilePathType::Universal(tp) => write!(f, "{}({})", UNIVERSAL, tile_path_name(tp))?,
+        }
+        write!(
+            f,
+            "@{},{}/{}",
+            self.coordinates.0,
+            self.coordinates.1,
+            rotation_name(self.rotation)
+        )
+    }
+}
+
+impl fmt::Display for TurnRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnRecord::Placements(placements) => {
+                for placement in placements {
+                    write!(f, "{} ", placement)?;
+                }
+                write!(f, "{}", END_TURN)
+            }
+            TurnRecord::CantPlay => write!(f, "{}", CANT_PLAY),
+        }
+    }
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match TilePath::try_from(*self) {
+            Ok(tile_path) => write!(f, "{}", tile_path_name(tile_path)),
+            Err(_) => write!(f, "{}", UNIVERSAL),
+        }
+    }
+}
+
+impl FromStr for Tile {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        let tile = if parser.peek_word() == UNIVERSAL {
+            parser.word();
+            Tile::Universal
+        } else {
+            Tile::from(parser.tile_path()?)
+        };
+        parser.skip_whitespace();
+        parser.end()?;
+        Ok(tile)
+    }
+}
+
+impl FromStr for TilePlacementEvent {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        let placement = parser.placement()?;
+        parser.skip_whitespace();
+        parser.end()?;
+        Ok(placement)
+    }
+}
+
+impl FromStr for TurnRecord {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        if parser.peek_word() == CANT_PLAY {
+            parser.word();
+            parser.skip_whitespace();
+            parser.end()?;
+            return Ok(TurnRecord::CantPlay);
+        }
+        let mut placements = Vec::new();
+        while !parser.is_at_end() {
+            if parser.peek_word() == END_TURN {
+                parser.word();
+                parser.skip_whitespace();
+                parser.end()?;
+                break;
+            }
+            placements.push(parser.placement()?);
+            parser.skip_whitespace();
+        }
+        if placements.is_empty() {
+            return Err(parser.error(format!(
+                "Expected at least one tile placement or `{}`",
+                CANT_PLAY
+            )));
+        }
+        Ok(TurnRecord::Placements(placements))
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            input,
+            position: 0,
+        }
+    }
+
+    fn error(&self, msg: String) -> NotationError {
+        NotationError {
+            position: self.position,
+            msg,
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.chars.len()
+    }
+
+    fn end(&self) -> Result<(), NotationError> {
+        if self.is_at_end() {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "Unexpected `{}` after the end of the turn",
+                self.chars[self.position..].iter().collect::<String>()
+            )))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek_word(&self) -> String {
+        self.chars[self.position.min(self.chars.len())..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect()
+    }
+
+    fn word(&mut self) -> String {
+        let word = self.peek_word();
+        self.position += word.chars().count();
+        word
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), NotationError> {
+        match self.chars.get(self.position) {
+            Some(c) if *c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Expected `{}`, found `{}`", expected, c))),
+            None => Err(self.error(format!(
+                "Expected `{}`, found the end of `{}`",
+                expected, self.input
+            ))),
+        }
+    }
+
+    fn placement(&mut self) -> Result<TilePlacementEvent, NotationError> {
+        let tile_path_type = self.tile_path_type()?;
+        self.expect('@')?;
+        let row = self.number()?;
+        self.expect(',')?;
+        let column = self.number()?;
+        self.expect('/')?;
+        let rotation = self.rotation()?;
+        Ok(TilePlacementEvent {
+            tile_path_type,
+            coordinates: Coordinates(row, column),
+            rotation,
+        })
+    }
+
+    fn tile_path_type(&mut self) -> Result<TilePathType, NotationError> {
+        if self.peek_word() == UNIVERSAL {
+            self.word();
+            self.expect('(')?;
+            let tile_path = self.tile_path()?;
+            self.expect(')')?;
+            Ok(TilePathType::Universal(tile_path))
+        } else {
+            self.tile_path().map(TilePathType::Normal)
+        }
+    }
+
+    fn tile_path(&mut self) -> Result<TilePath, NotationError> {
+        let start = self.position;
+        let word = self.word();
+        TILE_PATH_NAMES
+            .iter()
+            .find(|(_, name)| *name == word)
+            .map(|(tp, _)| *tp)
+            .ok_or_else(|| NotationError {
+                position: start,
+                msg: format!("Unknown tile `{}`", word),
+            })
+    }
+
+    fn rotation(&mut self) -> Result<Rotation, NotationError> {
+        let start = self.position;
+        let word = self.word();
+        ROTATION_NAMES
+            .iter()
+            .find(|(_, name)| *name == word)
+            .map(|(r, _)| *r)
+            .ok_or_else(|| NotationError {
+                position: start,
+                msg: format!("Unknown rotation `{}`", word),
+            })
+    }
+
+    fn number(&mut self) -> Result<i8, NotationError> {
+        let start = self.position;
+        if self.chars.get(self.position) == Some(&'-') {
+            self.position += 1;
+        }
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+        let number: String = self.chars[start..self.position].iter().collect();
+        number.parse().map_err(|_| NotationError {
+            position: start,
+            msg: if number.is_empty() {
+                "Expected a number".to_owned()
+            } else {
+                format!("Invalid coordinate `{}`", number)
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tile::TileBox;
+
+    #[test]
+    fn parse_turn() {
+        let target: TurnRecord = "Str@10,0/R0 Uni(L45)@10,1/R90 E".parse().unwrap();
+        assert_eq!(
+            target,
+            TurnRecord::Placements(vec![
+                TilePlacementEvent {
+                    tile_path_type: TilePathType::Normal(TilePath::Straight),
+                    coordinates: Coordinates(10, 0),
+                    rotation: Rotation::None,
+                },
+                TilePlacementEvent {
+                    tile_path_type: TilePathType::Universal(TilePath::Left45),
+                    coordinates: Coordinates(10, 1),
+                    rotation: Rotation::Clockwise90,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_cant_play() {
+        assert_eq!(" X ".parse::<TurnRecord>().unwrap(), TurnRecord::CantPlay);
+    }
+
+    #[test]
+    fn end_turn_is_optional() {
+        assert_eq!(
+            "R135@11,0/R270".parse::<TurnRecord>().unwrap(),
+            "R135@11,0/R270 E".parse::<TurnRecord>().unwrap()
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let notation = "Cnr90@3,-1/R180 Diag@4,0/R0 Uni(Ctr90)@5,0/R270 L135@5,21/R90 E";
+        let target: TurnRecord = notation.parse().unwrap();
+        assert_eq!(target.to_string(), notation);
+        assert_eq!(TurnRecord::CantPlay.to_string(), "X");
+    }
+
+    #[test]
+    fn unknown_tile_position() {
+        let res = "Str@10,0/R0 Foo@10,1/R0".parse::<TurnRecord>();
+        assert_eq!(
+            res,
+            Err(NotationError {
+                position: 12,
+                msg: "Unknown tile `Foo`".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_rotation_position() {
+        let res = "Str@10,0/R45".parse::<TurnRecord>();
+        assert!(matches!(res, Err(e) if e.position == 9 && e.msg.contains("rotation")));
+    }
+
+    #[test]
+    fn missing_separator_position() {
+        let res = "Uni(Str@10,0/R0".parse::<TurnRecord>();
+        assert!(matches!(res, Err(e) if e.position == 7 && e.msg.starts_with("Expected `)`")));
+    }
+
+    #[test]
+    fn missing_number() {
+        let res = "Str@,0/R0".parse::<TurnRecord>();
+        assert!(matches!(res, Err(e) if e.position == 4 && e.msg == "Expected a number"));
+    }
+
+    #[test]
+    fn text_after_end_turn() {
+        let res = "Str@10,0/R0 E Str@10,1/R0".parse::<TurnRecord>();
+        assert!(matches!(res, Err(e) if e.position == 14));
+    }
+
+    #[test]
+    fn empty_turn() {
+        assert!(matches!("".parse::<TurnRecord>(), Err(e) if e.position == 0));
+        assert!(matches!(" E".parse::<TurnRecord>(), Err(e) if e.position == 2));
+    }
+
+    #[test]
+    fn parse_placement() {
+        let target: TilePlacementEvent = "Uni(R45)@0,20/R180".parse().unwrap();
+        assert_eq!(target.to_string(), "Uni(R45)@0,20/R180");
+    }
+
+    #[test]
+    fn tile_round_trip() {
+        for tile in TileBox::all_tiles() {
+            assert_eq!(tile.to_string().parse::<Tile>(), Ok(tile));
+        }
+        assert_eq!("Uni".parse::<Tile>(), Ok(Tile::Universal));
+        assert!("Uni(Str)".parse::<Tile>().is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Spreads independent work across threads when built with the `parallel` feature. WebAssembly
+//! builds are always single-threaded. Either way results are returned in the order of the items,
+//! so callers get identical results with and without the feature.
+
+/// `items.into_iter().map(f).collect()`, with items mapped on rayon's thread pool
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+pub(crate) fn map<T, U, F>(items: Vec<T>, f: F) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Sync + Send,
+{
+    use rayon::prelude::*;
+
+    items.into_par_iter().map(f).collect()
+}
+
+/// `items.into_iter().map(f).collect()`
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+pub(crate) fn map<T, U, F>(items: Vec<T>, f: F) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Sync + Send,
+{
+    items.into_iter().map(f).collect()
+}
@@ -3,6 +3,7 @@ use crate::error::CellError;
 use crate::log::TilePlacementEvent;
 use crate::tile::{Coordinates, Rotation, Tile, ROTATIONS};
 
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::ops::{Add, Neg};
 
@@ -10,7 +11,7 @@ use std::ops::{Add, Neg};
 /// `crate::tile::Tile` except without the universal tile, because when placed,
 /// a universal tile must represent one of the standard `TilePath`s
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum TilePath {
     Straight,
     Diagonal,
@@ -34,7 +35,7 @@ pub static TILE_PATHS: [TilePath; 8] = [
 ];
 
 impl TilePath {
-    pub fn offsets(self) -> [Offset; 2] {
+    pub const fn offsets(self) -> [Offset; 2] {
         // Representations of the valid offsets as the cardinal directions making
         // the `TilePath` to `Offset` mappings easier to read
         const SW: Offset = Offset(1, -1);
@@ -56,8 +57,32 @@ impl TilePath {
             TilePath::Right135 => [S, SE],
         }
     }
+
+    /// `offsets` turned by `rotation`
+    pub fn rotated_offsets(self, rotation: Rotation) -> [Offset; 2] {
+        ROTATED_OFFSETS[self as usize][rotation as usize]
+    }
 }
 
+/// `TilePath::offsets` for every rotation, indexed by the `TilePath` and `Rotation`
+/// discriminants. Computed at compile time so finding where the river goes doesn't allocate
+const ROTATED_OFFSETS: [[[Offset; 2]; 4]; 8] = {
+    let mut table = [[[Offset(0, 0); 2]; 4]; 8];
+    let mut i = 0;
+    while i < TILE_PATHS.len() {
+        let [first, second] = TILE_PATHS[i].offsets();
+        let mut j = 0;
+        while j < ROTATIONS.len() {
+            let rotation = ROTATIONS[j];
+            table[TILE_PATHS[i] as usize][rotation as usize] =
+                [first.rotate(rotation), second.rotate(rotation)];
+            j += 1;
+        }
+        i += 1;
+    }
+    table
+};
+
 impl From<TilePath> for Tile {
     fn from(tp: TilePath) -> Self {
         match tp {
@@ -104,7 +129,7 @@ impl TryFrom<Tile> for TilePath {
 /// would like it to represent. For display purposes and because the player can
 /// change which path it represent, it is necessary to differentiate between,
 /// for example, a straight tile and a universal tile used as a straight path
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Eq))]
 pub enum TilePathType {
     Normal(TilePath),
@@ -119,6 +144,10 @@ impl TilePathType {
     pub fn offsets(&self) -> [Offset; 2] {
         TilePath::from(self).offsets()
     }
+
+    pub fn rotated_offsets(&self, rotation: Rotation) -> [Offset; 2] {
+        TilePath::from(self).rotated_offsets(rotation)
+    }
 }
 
 impl From<&TilePathType> for TilePath {
@@ -152,11 +181,11 @@ impl From<Tile> for TilePathType {
 /// tiles can "connect" to form the river.
 ///
 /// Both items of tuple must be of {1, 0, -1}. `Offset(0, 0)` is invalid.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Offset(pub i8, pub i8);
 
 impl Offset {
-    pub fn rotate(self, rotation: Rotation) -> Offset {
+    pub const fn rotate(self, rotation: Rotation) -> Offset {
         let Offset(x, y) = self;
         match rotation {
             Rotation::None => Offset(x, y),
@@ -169,6 +198,20 @@ impl Offset {
     pub fn is_diagonal(self) -> bool {
         self.0 != 0 && self.1 != 0
     }
+
+    /// Position in a 3x3 grid centered on `Offset(0, 0)`. Used to index tables of offsets
+    const fn grid_index(self) -> usize {
+        ((self.0 + 1) * 3 + self.1 + 1) as usize
+    }
+
+    // `PartialEq` and `Neg` can't be used at compile time
+    const fn same(self, other: Offset) -> bool {
+        self.0 == other.0 && self.1 == other.1
+    }
+
+    const fn reversed(self) -> Offset {
+        Offset(-self.0, -self.1)
+    }
 }
 
 impl Neg for Offset {
@@ -198,6 +241,24 @@ pub static OFFSETS: [Offset; 8] = [
     Offset(-1, 0),
 ];
 
+/// The offset the river leaves a tile with `tile_path` and `rotation` from, given the river
+/// enters it from `prev_coordinates`. `None` if neither end of the tile faces `prev_coordinates`
+pub(crate) fn exit_offset(
+    prev_coordinates: Coordinates,
+    coordinates: Coordinates,
+    tile_path: TilePath,
+    rotation: Rotation,
+) -> Option<Offset> {
+    let [first, second] = tile_path.rotated_offsets(rotation);
+    if coordinates + first == prev_coordinates {
+        Some(second)
+    } else if coordinates + second == prev_coordinates {
+        Some(first)
+    } else {
+        None
+    }
+}
+
 /// Given the previous placement, evaluates and validates a single tile
 /// placement. The return value of this function can be fed to its next call.
 pub fn eval_placement(
@@ -212,55 +273,57 @@ pub fn eval_placement(
             "Tile at doesn't align with the rest of the river".to_owned(),
         ));
     }
-    let offsets: Vec<Offset> = placement
-        .tile_path_type
-        .offsets()
-        .iter()
-        .map(|o| o.rotate(placement.rotation))
-        .collect();
-    let rev_offset = offsets
-        .iter()
-        .find(|o| placement.coordinates + **o == prev.0)
-        .ok_or_else(|| {
-            CellError::new(
-                placement.coordinates,
-                "Tile and rotation don't align with the rest of the river".to_owned(),
-            )
-        })?;
-    offsets
-        .iter()
-        // Assumes there's only two offsets because `offsets` comes from `[Offset; 2]`
-        .find(|o| *o != rev_offset)
-        .ok_or_else(|| {
-            CellError::new(
-                placement.coordinates,
-                "No valid output river path offset".to_owned(),
-            )
-        })
-        .map(|o| (new_coordinates, *o))
+    exit_offset(
+        prev.0,
+        placement.coordinates,
+        TilePath::from(&placement.tile_path_type),
+        placement.rotation,
+    )
+    .map(|offset| (new_coordinates, offset))
+    .ok_or_else(|| {
+        CellError::new(
+            placement.coordinates,
+            "Tile and rotation don't align with the rest of the river".to_owned(),
+        )
+    })
 }
 
 /// Returns an `Option`, but it should only return `None` in cases where the set
 /// of offsets are invalid, e.g. `Offset(1, 0)` and `Offset(-1, 0)`. This set of
 /// offsets is invalid because the river would cross over itself.
-// TODO: this could probably be memoized, there's 8 x 8 unique offset pairs. Need to
-//       measure first.
 pub fn offsets_to_tile_placement(prev_offset: Offset, new_offset: Offset) -> Option<TilePlacement> {
-    TILE_PATHS.iter().find_map(|tp| {
-        ROTATIONS.iter().find_map(|r| {
-            let offsets: Vec<_> = tp.offsets().iter().map(|o| o.rotate(*r)).collect();
+    TURN_PLACEMENTS[prev_offset.grid_index()][new_offset.grid_index()]
+        .map(|(tile_path, rotation)| TilePlacement::new(TilePathType::Normal(tile_path), rotation))
+}
+
+/// The first tile path and rotation, in `TILE_PATHS` and `ROTATIONS` order, that joins each pair
+/// of offsets, indexed by `Offset::grid_index`
+const TURN_PLACEMENTS: [[Option<(TilePath, Rotation)>; 9]; 9] = {
+    let mut table = [[None; 9]; 9];
+    let mut i = 0;
+    while i < OFFSETS.len() {
+        let mut j = 0;
+        while j < OFFSETS.len() {
             // `prev_offset` is flipped because the placement we're looking for is on the
             // "receiving" end of the previous tile's offset
-            if (offsets[0] == -prev_offset && offsets[1] == new_offset)
-                || (offsets[0] == new_offset && offsets[1] == -prev_offset)
-            {
-                Some(TilePlacement::new(TilePathType::Normal(*tp), *r))
-            } else {
-                None
+            let (from, to) = (OFFSETS[i].reversed(), OFFSETS[j]);
+            let mut found = None;
+            let mut k = 0;
+            while found.is_none() && k < TILE_PATHS.len() * ROTATIONS.len() {
+                let (tile_path, rotation) = (TILE_PATHS[k / 4], ROTATIONS[k % 4]);
+                let [first, second] = ROTATED_OFFSETS[tile_path as usize][rotation as usize];
+                if (first.same(from) && second.same(to)) || (first.same(to) && second.same(from)) {
+                    found = Some((tile_path, rotation));
+                }
+                k += 1;
             }
-        })
-    })
-}
+            table[OFFSETS[i].grid_index()][OFFSETS[j].grid_index()] = found;
+            j += 1;
+        }
+        i += 1;
+    }
+    table
+};
 
 #[cfg(test)]
 mod test {
@@ -343,10 +406,7 @@ mod test {
 
     #[test]
     fn offsets_to_tile_placement_none() {
-        assert!(matches!(
-            offsets_to_tile_placement(Offset(1, 0), Offset(-1, 0)),
-            None
-        ));
+        assert!(offsets_to_tile_placement(Offset(1, 0), Offset(-1, 0)).is_none());
     }
 
     #[test]
@@ -369,4 +429,35 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn tables_match_rotating_offsets() {
+        for tile_path in TILE_PATHS.iter() {
+            for rotation in ROTATIONS.iter() {
+                let [first, second] = tile_path.offsets();
+                assert_eq!(
+                    tile_path.rotated_offsets(*rotation),
+                    [first.rotate(*rotation), second.rotate(*rotation)]
+                );
+            }
+        }
+        for prev_offset in OFFSETS.iter() {
+            for new_offset in OFFSETS.iter() {
+                let expected = TILE_PATHS.iter().find_map(|tp| {
+                    ROTATIONS.iter().find_map(|r| {
+                        let offsets = tp.rotated_offsets(*r);
+                        (offsets == [-*prev_offset, *new_offset]
+                            || offsets == [*new_offset, -*prev_offset])
+                        .then(|| TilePlacement::new(TilePathType::Normal(*tp), *r))
+                    })
+                });
+                assert_eq!(
+                    offsets_to_tile_placement(*prev_offset, *new_offset),
+                    expected
+                );
+                // Every turn except back the way the river came has a tile
+                assert_eq!(expected.is_none(), *new_offset == -*prev_offset);
+            }
+        }
+    }
 }
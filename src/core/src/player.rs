@@ -1,14 +1,76 @@
-use crate::score::TurnScore;
+use crate::ai::{Brute, CPUPlayer, Difficulty};
+use crate::rules::RuleSet;
+use crate::score::{ScoreBreakdown, TurnScore};
 use crate::tile::{Tile, TileBox};
 
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-const MAX_TILES: usize = 5;
+/// Rack size of the standard rules
+pub(crate) const MAX_TILES: usize = 5;
 
 pub type TileArray = SmallVec<[Tile; MAX_TILES]>;
 
+/// Who takes a seat in a new game. Seats are filled in order
+#[derive(Clone, Debug)]
+pub enum PlayerSetup {
+    Human {
+        name: String,
+    },
+    Cpu {
+        name: String,
+        /// Plays every turn of this player for the whole game
+        strategy: Box<dyn CPUPlayer>,
+    },
+}
+
+impl PlayerSetup {
+    pub fn human(name: String) -> Self {
+        Self::Human { name }
+    }
+
+    pub fn cpu(name: String, strategy: Box<dyn CPUPlayer>) -> Self {
+        Self::Cpu { name, strategy }
+    }
+
+    /// `player_names` human players followed by `cpu_player_count` CPU players named “cpu1”,
+    /// “cpu2”, etc. that use the default strategy
+    pub fn humans_and_cpus(player_names: Vec<String>, cpu_player_count: u8) -> Vec<Self> {
+        let player_count = player_names.len() + cpu_player_count as usize;
+        player_names
+            .into_iter()
+            .map(Self::human)
+            .chain(
+                (1..=cpu_player_count)
+                    .map(|i| Self::cpu(format!("cpu{}", i), Box::new(Brute::new(player_count)))),
+            )
+            .collect()
+    }
+
+    /// `player_names` human players followed by a CPU player for each of `cpu_difficulties`,
+    /// named “cpu1”, “cpu2”, etc. Each CPU player's choices are seeded from `seed` and its seat,
+    /// so passing the game's seed makes the whole game reproducible
+    pub fn humans_and_cpus_with_difficulties(
+        player_names: Vec<String>,
+        cpu_difficulties: &[Difficulty],
+        seed: u64,
+    ) -> Vec<Self> {
+        let human_count = player_names.len();
+        let player_count = human_count + cpu_difficulties.len();
+        player_names
+            .into_iter()
+            .map(Self::human)
+            .chain(cpu_difficulties.iter().enumerate().map(|(i, difficulty)| {
+                let seat = (human_count + i) as u64;
+                let brute = Brute::with_difficulty(player_count, *difficulty, seed ^ seat);
+                Self::cpu(format!("cpu{}", i + 1), Box::new(brute))
+            }))
+            .collect()
+    }
+}
+
 /// Holds all data related to a single player
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Player {
     name: String,
     tile_rack: TileArray,
@@ -16,45 +78,49 @@ pub struct Player {
     scores: Vec<TurnScore>,
     /// Scores of current turn
     current_turn_score: TurnScore,
+    /// Where each of `scores` came from
+    breakdowns: Vec<ScoreBreakdown>,
+    current_turn_breakdown: ScoreBreakdown,
     /// Computer-controlled player
     is_cpu: bool,
 }
 
 impl Player {
-    pub(crate) fn new(name: String, tile_box: &mut TileBox, is_cpu: bool) -> Self {
+    pub(crate) fn new(name: String, tile_box: &mut TileBox, is_cpu: bool, rules: &RuleSet) -> Self {
         let mut tile_rack = TileArray::new();
-        Self::fill_rack(&mut tile_rack, tile_box);
+        Self::fill_rack(&mut tile_rack, tile_box, rules.rack_size);
         Self {
             name,
             tile_rack,
             scores: Vec::new(),
             current_turn_score: TurnScore::default(),
+            breakdowns: Vec::new(),
+            current_turn_breakdown: ScoreBreakdown::default(),
             is_cpu,
         }
     }
 
-    /// Refill the player's `tile_rack` and return the total score of the tiles they played in
-    /// the current turn.
-    pub(crate) fn end_turn(&mut self, tile_box: &mut TileBox) -> TurnScore {
-        if self.tile_rack.is_empty() {
-            // TODO: this should possibly only apply if the player began
-            // their turn with 5 tiles
-            // Bonus for using all tiles
-            self.add_score(TurnScore::from(20));
-        }
-        let final_turn_score = self.current_turn_score;
-        Self::fill_rack(&mut self.tile_rack, tile_box);
-        self.scores.push(final_turn_score);
-        self.current_turn_score = TurnScore::default();
-        final_turn_score
+    /// Return the total score of the `played` tiles the player played in the current turn,
+    /// including the bonus for playing all their tiles. Their rack is refilled separately with
+    /// `draw_tiles`, unless the turn ended the game.
+    pub(crate) fn end_turn(&mut self, rules: &RuleSet, played: usize) -> TurnScore {
+        self.add_score(ScoreBreakdown {
+            all_tiles: rules.all_tiles_bonus(self.tile_rack.len() + played, played),
+            ..ScoreBreakdown::default()
+        });
+        self.commit_turn_score()
+    }
+
+    pub(crate) fn draw_tiles(&mut self, tile_box: &mut TileBox, rules: &RuleSet) {
+        Self::fill_rack(&mut self.tile_rack, tile_box, rules.rack_size);
     }
 
     pub fn rack_is_empty(&self) -> bool {
         self.tile_rack.is_empty()
     }
 
-    fn fill_rack(tile_rack: &mut TileArray, tile_box: &mut TileBox) {
-        while tile_rack.len() < MAX_TILES {
+    fn fill_rack(tile_rack: &mut TileArray, tile_box: &mut TileBox, rack_size: usize) {
+        while tile_rack.len() < rack_size {
             if let Some(tile) = tile_box.draw() {
                 tile_rack.push(tile);
             } else {
@@ -83,19 +149,34 @@ impl Player {
     }
 
     /// The player can't play any tiles and is ending their turn. Discards all their current tiles
-    /// and refills their `tile_rack` from `tile_box`.
-    pub(crate) fn cant_play(&mut self, tile_box: &mut TileBox) -> TurnScore {
+    /// into `tile_box`. Call `draw_tiles` to refill their `tile_rack` unless the game has ended
+    pub(crate) fn cant_play(&mut self, tile_box: &mut TileBox, rules: &RuleSet) -> TurnScore {
         let tiles = self.discard_tiles();
-        let tile_score = tiles.iter().fold(0, |acc, t| acc + t.score());
-        let turn_score = TurnScore {
-            add: 0,
-            sub: tile_score,
-        };
-        self.add_score(turn_score);
+        self.add_score(ScoreBreakdown {
+            cant_play: rules.cant_play_penalty(&tiles),
+            ..ScoreBreakdown::default()
+        });
         tile_box.discard(tiles);
-        Self::fill_rack(&mut self.tile_rack, tile_box);
+        self.commit_turn_score()
+    }
+
+    /// Final scoring at the end of the game. Subtracts the value of the tiles left in the rack
+    /// as a separate entry in `scores`
+    pub(crate) fn settle_rack(&mut self) -> TurnScore {
+        self.add_score(ScoreBreakdown {
+            rack_remainder: self.tile_rack.iter().map(|t| t.score()).sum(),
+            ..ScoreBreakdown::default()
+        });
+        self.commit_turn_score()
+    }
+
+    /// Moves the current turn's score to the completed turns
+    fn commit_turn_score(&mut self) -> TurnScore {
+        let turn_score = self.current_turn_score;
         self.scores.push(turn_score);
+        self.breakdowns.push(self.current_turn_breakdown);
         self.current_turn_score = TurnScore::default();
+        self.current_turn_breakdown = ScoreBreakdown::default();
         turn_score
     }
 
@@ -109,8 +190,9 @@ impl Player {
     }
 
     /// Modify the current turn score and return the updated turn score
-    pub(crate) fn add_score(&mut self, score: TurnScore) -> TurnScore {
-        self.current_turn_score += score;
+    pub(crate) fn add_score(&mut self, score: ScoreBreakdown) -> TurnScore {
+        self.current_turn_breakdown += score;
+        self.current_turn_score += score.turn_score();
         self.current_turn_score
     }
 
@@ -130,6 +212,20 @@ impl Player {
         self.current_turn_score
     }
 
+    /// Where the points of each of `scores` came from
+    pub fn score_breakdowns(&self) -> &[ScoreBreakdown] {
+        &self.breakdowns
+    }
+
+    pub fn current_turn_breakdown(&self) -> ScoreBreakdown {
+        self.current_turn_breakdown
+    }
+
+    /// Where all the points of completed turns came from
+    pub fn total_breakdown(&self) -> ScoreBreakdown {
+        self.breakdowns.iter().copied().sum()
+    }
+
     pub fn total_score(&self) -> i16 {
         self.scores
             .iter()
@@ -141,56 +237,155 @@ impl Player {
     }
 }
 
+#[cfg(test)]
+impl Player {
+    /// Test helper for replacing the player's rack
+    pub(crate) fn set_tiles(&mut self, tiles: TileArray) {
+        self.tile_rack = tiles;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rules::{AllTilesBonus, CantPlayPenalty};
+
+    use smallvec::smallvec;
 
     fn setup() -> (TileBox, Player) {
         let mut tile_box = TileBox::default();
-        let target = Player::new("Test".to_owned(), &mut tile_box, false);
+        let target = Player::new("Test".to_owned(), &mut tile_box, false, &RuleSet::default());
         (tile_box, target)
     }
 
     #[test]
     fn adding_score_changes_score() {
         let (_, mut target) = setup();
-        let turn_score = TurnScore { add: 7, sub: 7 };
-        let updated_score = target.add_score(turn_score);
-        assert_eq!(updated_score, turn_score);
+        let score = ScoreBreakdown {
+            tiles: 7,
+            penalty_cells: 7,
+            ..ScoreBreakdown::default()
+        };
+        let updated_score = target.add_score(score);
+        assert_eq!(updated_score, TurnScore { add: 7, sub: 7 });
+        assert_eq!(target.current_turn_breakdown(), score);
     }
 
     /// Adding opposite scores returns the original score
     #[test]
     fn opposite_returns_original_score() {
         let (_, mut target) = setup();
-        let score = TurnScore { add: 30, sub: 10 };
+        let score = ScoreBreakdown {
+            tiles: 30,
+            penalty_cells: 10,
+            ..ScoreBreakdown::default()
+        };
         let mut current_score = target.add_score(score);
-        assert_eq!(current_score, score);
+        assert_eq!(current_score, TurnScore { add: 30, sub: 10 });
         current_score = target.add_score(-score);
         assert_eq!(current_score, TurnScore { add: 0, sub: 0 });
+        assert_eq!(target.current_turn_breakdown(), ScoreBreakdown::default());
     }
 
     #[test]
     fn end_turn_updates_scores() {
-        let (mut tile_box, mut target) = setup();
+        let (_, mut target) = setup();
         assert_eq!(target.scores, []);
-        target.add_score(TurnScore { add: 10, sub: 10 });
-        let current_score = target.add_score(TurnScore { add: 25, sub: 60 });
+        target.add_score(ScoreBreakdown {
+            tiles: 10,
+            penalty_cells: 10,
+            ..ScoreBreakdown::default()
+        });
+        let current_score = target.add_score(ScoreBreakdown {
+            tiles: 10,
+            bonus_cells: 15,
+            penalty_cells: 60,
+            ..ScoreBreakdown::default()
+        });
         assert_eq!(current_score, TurnScore { add: 35, sub: 70 });
-        assert_eq!(current_score, target.end_turn(&mut tile_box));
+        assert_eq!(current_score, target.end_turn(&RuleSet::default(), 0));
         assert_eq!(target.scores, vec![TurnScore { add: 35, sub: 70 }]);
+        assert_eq!(
+            target.score_breakdowns(),
+            [ScoreBreakdown {
+                tiles: 20,
+                bonus_cells: 15,
+                penalty_cells: 70,
+                ..ScoreBreakdown::default()
+            }]
+        );
+        assert_eq!(target.current_turn_breakdown(), ScoreBreakdown::default());
     }
 
     #[test]
     fn end_turn_adds_used_all_tiles_bonus() {
-        let (mut tile_box, mut target) = setup();
+        let (_, mut target) = setup();
         for tile in target.tiles().clone() {
             target.place_tile(tile);
         }
         assert_eq!(
             TurnScore { add: 20, sub: 0 },
-            target.end_turn(&mut tile_box)
+            target.end_turn(&RuleSet::default(), MAX_TILES)
+        );
+    }
+
+    #[test]
+    fn full_rack_bonus_needs_full_rack() {
+        let (mut tile_box, mut target) = setup();
+        let rules = RuleSet {
+            all_tiles_bonus: 15,
+            all_tiles_bonus_when: AllTilesBonus::FullRack,
+            ..RuleSet::default()
+        };
+        target.set_tiles(smallvec![Tile::Straight, Tile::Diagonal]);
+        target.place_tile(Tile::Straight);
+        target.place_tile(Tile::Diagonal);
+        assert_eq!(TurnScore::default(), target.end_turn(&rules, 2));
+        target.draw_tiles(&mut tile_box, &rules);
+        for tile in target.tiles().clone() {
+            target.place_tile(tile);
+        }
+        assert_eq!(
+            TurnScore { add: 15, sub: 0 },
+            target.end_turn(&rules, MAX_TILES)
+        );
+    }
+
+    #[test]
+    fn cant_play_fixed_penalty() {
+        let (mut tile_box, mut target) = setup();
+        let rules = RuleSet {
+            cant_play_penalty: CantPlayPenalty::Fixed(10),
+            rack_size: 3,
+            ..RuleSet::default()
+        };
+        let res = target.cant_play(&mut tile_box, &rules);
+        assert_eq!(res.score(), -10);
+        assert!(target.rack_is_empty());
+        target.draw_tiles(&mut tile_box, &rules);
+        assert_eq!(target.tiles().len(), 3);
+    }
+
+    #[test]
+    fn settle_rack_adds_entry() {
+        let (_, mut target) = setup();
+        target.set_tiles(smallvec![Tile::Universal, Tile::Straight]);
+        let expected = Tile::Universal.score() + Tile::Straight.score();
+        assert_eq!(
+            target.settle_rack(),
+            TurnScore {
+                add: 0,
+                sub: expected
+            }
+        );
+        assert_eq!(
+            target.scores,
+            vec![TurnScore {
+                add: 0,
+                sub: expected
+            }]
         );
+        assert_eq!(target.total_score(), -expected);
     }
 
     #[test]
@@ -198,7 +393,7 @@ mod test {
         let (mut tile_box, mut target) = setup();
         assert_eq!(target.scores, []);
         let expected_score = target.tiles().iter().fold(0i16, |acc, t| acc - t.score());
-        let res = target.cant_play(&mut tile_box);
+        let res = target.cant_play(&mut tile_box, &RuleSet::default());
         assert_eq!(res.score(), expected_score);
         // Previously can't play would return the correct score but store a score with an added 20
         // points for using all tiles.
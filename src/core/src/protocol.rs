@@ -0,0 +1,517 @@
+//! Line-based text protocol for CPU players that run as separate programs, similar in spirit to
+//! chess's UCI. The engine writes to the bot's stdin and reads the bot's replies from its stdout:
+//!
+//! ```text
+//! engine: nile 2
+//! bot:    ready brute
+//! engine: position
+//! engine: rules {"cant_play_check":"Reject","river_check":"Reachable",...}
+//! engine: bonuses 1,2=-10 3,5=20 ...
+//! engine: end 0 0 0 ...
+//! engine: tiles Str@10,0/R0 Uni(L45)@10,1/R90
+//! engine: last 10,1 -1,1
+//! engine: rack Str Diag R45 L135 Uni
+//! engine: scores 12 30
+//! engine: unseen L135=9 Ctr90=10 ...
+//! engine: go 1
+//! bot:    turn 1 Diag@9,2/R0 R45@8,3/R0
+//! engine: quit
+//! ```
+//!
+//! * `nile <version>` opens the session. The bot replies `ready <name>`
+//! * `position` … `go <id>` asks for a turn. `<id>` is a number that's different for each
+//!   request:
+//!   * `rules` is the game's `RuleSet` as JSON on a single line
+//!   * `bonuses` lists the board's nonzero cell bonuses as `<row>,<column>=<bonus>`
+//!   * `end` lists the bonuses of the end-of-game column from top to bottom
+//!   * `tiles` lists the tiles on the board in turn notation
+//!   * `last` is the coordinates of the last tile of the river and the direction it flows out of
+//!     that tile
+//!   * `rack` lists the bot's tiles, with `Uni` for universal tiles
+//!   * `scores` is the bot's score followed by the other players' in turn order, starting with
+//!     the player after the bot
+//!   * `unseen` counts the tiles the bot hasn't seen, i.e. those in the tile box or in the other
+//!     players' racks
+//! * The bot replies `turn <id> <turn>` in turn notation, where `X` means it can't play. The
+//!   engine ignores replies with the id of an earlier request, e.g. one that timed out
+//! * `quit` ends the session
+//!
+//! The bot replies `error <message>` to anything it can't handle, or `error <id> <message>` to a
+//! position it can't handle.
+use crate::ai::CPUPlayer;
+use crate::board::{Board, TilePlacement, BOARD_DIM};
+use crate::layout::{BoardLayout, CellBonus};
+use crate::log::TilePlacementEvent;
+use crate::path::Offset;
+use crate::player::TileArray;
+use crate::record::TurnRecord;
+use crate::rules::RuleSet;
+use crate::tile::{Coordinates, Tile, TileBox};
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// Version 2 added the `rules` line. Version 3 added request ids
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Everything a bot is told before taking a turn
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub board: Board,
+    pub rules: RuleSet,
+    pub rack: TileArray,
+    /// The bot's score followed by the other players' in turn order, starting with the next player
+    pub scores: Vec<i16>,
+    /// Tiles in the tile box or the other players' racks, with how many there are of each
+    pub unseen: Vec<(Tile, usize)>,
+}
+
+impl Position {
+    pub fn new(
+        board: &Board,
+        rules: &RuleSet,
+        rack: &TileArray,
+        score: i16,
+        other_scores: &[i16],
+    ) -> Self {
+        let mut unseen: Vec<(Tile, usize)> = Vec::new();
+        for tile in crate::ai::unseen_tiles(board, rack) {
+            match unseen.iter_mut().find(|(t, _)| *t == tile) {
+                Some((_, count)) => *count += 1,
+                None => unseen.push((tile, 1)),
+            }
+        }
+        // Same order as the tile box
+        let order = TileBox::all_tiles();
+        unseen.sort_by_key(|(tile, _)| order.iter().position(|t| t == tile));
+        Self {
+            board: board.clone(),
+            rules: rules.clone(),
+            rack: rack.clone(),
+            scores: std::iter::once(score)
+                .chain(other_scores.iter().copied())
+                .collect(),
+            unseen,
+        }
+    }
+}
+
+fn join<T: fmt::Display>(keyword: &str, items: impl Iterator<Item = T>) -> String {
+    items.fold(keyword.to_owned(), |line, item| {
+        format!("{} {}", line, item)
+    })
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bonuses = (0..BOARD_DIM as i8)
+            .flat_map(|row| (0..BOARD_DIM as i8).map(move |column| Coordinates(row, column)))
+            .filter_map(|coordinates| {
+                let bonus = self.board.cell(coordinates)?.bonus();
+                (bonus != 0).then(|| format!("{},{}={}", coordinates.0, coordinates.1, bonus))
+            });
+        let end_column = (0..BOARD_DIM as i8).map(|row| {
+            self.board
+                .cell(Coordinates(row, BOARD_DIM as i8))
+                .map_or(0, |cell| cell.bonus())
+        });
+        let tiles = self
+            .board
+            .tiles()
+            .map(|(coordinates, tile_placement)| TilePlacementEvent {
+                tile_path_type: *tile_placement.tile_path_type(),
+                coordinates,
+                rotation: tile_placement.rotation(),
+            });
+        let (Coordinates(row, column), Offset(row_offset, column_offset)) =
+            self.board.last_placement();
+        writeln!(f, "position")?;
+        let rules = serde_json::to_string(&self.rules).map_err(|_| fmt::Error)?;
+        writeln!(f, "rules {}", rules)?;
+        writeln!(f, "{}", join("bonuses", bonuses))?;
+        writeln!(f, "{}", join("end", end_column))?;
+        writeln!(f, "{}", join("tiles", tiles))?;
+        writeln!(
+            f,
+            "last {},{} {},{}",
+            row, column, row_offset, column_offset
+        )?;
+        writeln!(f, "{}", join("rack", self.rack.iter()))?;
+        writeln!(f, "{}", join("scores", self.scores.iter()))?;
+        let unseen = self
+            .unseen
+            .iter()
+            .map(|(tile, count)| format!("{}={}", tile, count));
+        writeln!(f, "{}", join("unseen", unseen))
+    }
+}
+
+fn parse_pair(s: &str) -> Result<(i8, i8), String> {
+    let invalid = || format!("Invalid coordinates `{}`", s);
+    let (row, column) = s.split_once(',').ok_or_else(invalid)?;
+    Ok((
+        row.parse().map_err(|_| invalid())?,
+        column.parse().map_err(|_| invalid())?,
+    ))
+}
+
+fn parse_number<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid number `{}`", s))
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    /// Parses the lines from `position` up to `go`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some("position") {
+            return Err("Expected `position`".to_owned());
+        }
+        let mut rules = None;
+        let mut bonuses = None;
+        let mut end_column = None;
+        let mut tiles = None;
+        let mut last_placement = None;
+        let mut rack = None;
+        let mut scores = None;
+        let mut unseen = None;
+        for line in lines {
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            match keyword {
+                "rules" => {
+                    let json = line[keyword.len()..].trim();
+                    rules = Some(
+                        serde_json::from_str(json)
+                            .map_err(|e| format!("Invalid rules `{}`: {}", json, e))?,
+                    )
+                }
+                "bonuses" => {
+                    bonuses = Some(
+                        words
+                            .map(|word| {
+                                let (coordinates, bonus) = word
+                                    .split_once('=')
+                                    .ok_or_else(|| format!("Invalid bonus `{}`", word))?;
+                                let (row, column) = parse_pair(coordinates)?;
+                                Ok(CellBonus {
+                                    coordinates: Coordinates(row, column),
+                                    bonus: parse_number(bonus)?,
+                                })
+                            })
+                            .collect::<Result<Vec<_>, String>>()?,
+                    )
+                }
+                "end" => end_column = Some(words.map(parse_number).collect::<Result<_, _>>()?),
+                "tiles" => {
+                    tiles = Some(
+                        words
+                            .map(|word| {
+                                word.parse::<TilePlacementEvent>()
+                                    .map_err(|e| format!("Invalid tile `{}`: {}", word, e))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                }
+                "last" => {
+                    let (coordinates, offset) = match (words.next(), words.next()) {
+                        (Some(coordinates), Some(offset)) => (coordinates, offset),
+                        _ => return Err(format!("Invalid `{}`", line)),
+                    };
+                    let (row, column) = parse_pair(coordinates)?;
+                    let (row_offset, column_offset) = parse_pair(offset)?;
+                    last_placement =
+                        Some((Coordinates(row, column), Offset(row_offset, column_offset)));
+                }
+                "rack" => {
+                    rack = Some(
+                        words
+                            .map(|word| {
+                                word.parse::<Tile>()
+                                    .map_err(|e| format!("Invalid tile `{}`: {}", word, e))
+                            })
+                            .collect::<Result<TileArray, _>>()?,
+                    )
+                }
+                "scores" => scores = Some(words.map(parse_number).collect::<Result<_, _>>()?),
+                "unseen" => {
+                    unseen = Some(
+                        words
+                            .map(|word| {
+                                let (tile, count) = word
+                                    .split_once('=')
+                                    .ok_or_else(|| format!("Invalid tile count `{}`", word))?;
+                                let tile = tile
+                                    .parse::<Tile>()
+                                    .map_err(|e| format!("Invalid tile `{}`: {}", tile, e))?;
+                                Ok((tile, parse_number(count)?))
+                            })
+                            .collect::<Result<Vec<_>, String>>()?,
+                    )
+                }
+                _ => return Err(format!("Unknown position field `{}`", keyword)),
+            }
+        }
+        let missing = |field: &str| format!("Missing `{}`", field);
+        let layout = BoardLayout {
+            bonuses: bonuses.ok_or_else(|| missing("bonuses"))?,
+            end_column: end_column.ok_or_else(|| missing("end"))?,
+            // Replaced by `last`
+            start: BoardLayout::default().start,
+        };
+        let mut board = Board::from_layout(&layout)?;
+        for tile in tiles.ok_or_else(|| missing("tiles"))? {
+            board
+                .place_tile(
+                    tile.coordinates,
+                    TilePlacement::new(tile.tile_path_type, tile.rotation),
+                )
+                .map_err(|e| e.msg)?;
+        }
+        board.set_last_placement(last_placement.ok_or_else(|| missing("last"))?);
+        let scores: Vec<i16> = scores.ok_or_else(|| missing("scores"))?;
+        if scores.is_empty() {
+            return Err("Expected at least one score".to_owned());
+        }
+        Ok(Self {
+            board,
+            rules: rules.ok_or_else(|| missing("rules"))?,
+            rack: rack.ok_or_else(|| missing("rack"))?,
+            scores,
+            unseen: unseen.ok_or_else(|| missing("unseen"))?,
+        })
+    }
+}
+
+/// Plays as a bot over the protocol, reading requests from `input` and writing replies to
+/// `output` until `quit` or the end of `input`. The strategy is created with the number of
+/// players when the first position is received
+pub fn run_bot(
+    name: &str,
+    new_strategy: impl Fn(usize) -> Box<dyn CPUPlayer>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut strategy: Option<Box<dyn CPUPlayer>> = None;
+    let mut lines = input.lines();
+    while let Some(line) = lines.next() {
+        let line = line?;
+        match line.split_whitespace().next() {
+            None => continue,
+            Some("quit") => break,
+            Some("nile") => {
+                if line.trim() == format!("nile {}", PROTOCOL_VERSION) {
+                    writeln!(output, "ready {}", name)?;
+                } else {
+                    writeln!(output, "error Unsupported protocol `{}`", line.trim())?;
+                }
+            }
+            Some("position") => {
+                let mut request = line.clone();
+                let mut id = None;
+                for line in lines.by_ref() {
+                    let line = line?;
+                    let mut words = line.split_whitespace();
+                    if words.next() == Some("go") {
+                        id = words.next().map(str::to_owned);
+                        break;
+                    }
+                    request = request + "\n" + &line;
+                }
+                let id = match id {
+                    Some(id) => id,
+                    None => {
+                        writeln!(output, "error Expected `go <id>`")?;
+                        output.flush()?;
+                        continue;
+                    }
+                };
+                match request.parse::<Position>() {
+                    Ok(position) => {
+                        let strategy =
+                            strategy.get_or_insert_with(|| new_strategy(position.scores.len()));
+                        let turn = strategy
+                            .take_turn(
+                                &position.rack,
+                                &position.board,
+                                &position.rules,
+                                position.scores[0],
+                                position.scores[1..].to_vec(),
+                            )
+                            .into_iter()
+                            .next()
+                            .map_or(TurnRecord::CantPlay, TurnRecord::Placements);
+                        writeln!(output, "turn {} {}", id, turn)?;
+                    }
+                    Err(e) => writeln!(output, "error {} {}", id, e)?,
+                }
+            }
+            Some(command) => writeln!(output, "error Unknown command `{}`", command)?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ai::Brute;
+    use crate::moves::play_turn;
+    use crate::nile::Nile;
+    use crate::path::{TilePath, TilePathType};
+    use crate::player::PlayerSetup;
+    use crate::rules::RiverCheck;
+    use crate::tile::Rotation;
+
+    use smallvec::smallvec;
+
+    fn position() -> Position {
+        let mut board = Board::new();
+        play_turn(
+            &mut board,
+            &[TilePlacementEvent {
+                tile_path_type: TilePathType::Universal(TilePath::Straight),
+                coordinates: Coordinates(10, 0),
+                rotation: Rotation::None,
+            }],
+            RiverCheck::default(),
+        )
+        .unwrap();
+        Position::new(
+            &board,
+            &RuleSet {
+                river_check: RiverCheck::Escape,
+                ..RuleSet::default()
+            },
+            &smallvec![Tile::Straight, Tile::Universal, Tile::Right135],
+            8,
+            &[-4, 20],
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let target = position();
+        let parsed: Position = target.to_string().parse().unwrap();
+        assert_eq!(parsed.to_string(), target.to_string());
+        assert_eq!(parsed.board.last_placement(), target.board.last_placement());
+        assert_eq!(parsed.rules, target.rules);
+        assert_eq!(parsed.rack, target.rack);
+        assert_eq!(parsed.scores, vec![8, -4, 20]);
+
+        // From the second seat of three, the third player is next and the first is last
+        let mut nile = Nile::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 3),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(3),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            nile.play_cpu_turn().unwrap();
+        }
+        while nile.current_turn() != 1 {
+            nile.play_cpu_turn().unwrap();
+        }
+        let scores: Vec<i16> = nile.players().iter().map(|p| p.total_score()).collect();
+        let target = Position::new(
+            nile.board(),
+            &RuleSet::default(),
+            nile.current_player().tiles(),
+            scores[nile.current_turn()],
+            &nile.other_player_scores(),
+        );
+        let parsed: Position = target.to_string().parse().unwrap();
+        assert_ne!(scores[0], scores[2]);
+        assert_eq!(parsed.scores, vec![scores[1], scores[2], scores[0]]);
+    }
+
+    #[test]
+    fn counts_unseen_tiles() {
+        let target = position();
+        let count = |tile| {
+            target
+                .unseen
+                .iter()
+                .find(|(t, _)| *t == tile)
+                .map_or(0, |(_, count)| *count)
+        };
+        assert_eq!(count(Tile::Universal), 2);
+        assert_eq!(count(Tile::Straight), 19);
+        assert_eq!(count(Tile::Right135), 9);
+        assert_eq!(
+            target.unseen.iter().map(|(_, count)| count).sum::<usize>(),
+            TileBox::all_tiles().len() - 4
+        );
+    }
+
+    #[test]
+    fn missing_field() {
+        let text = position().to_string().replace("\nrack ", "\nother ");
+        assert_eq!(
+            text.parse::<Position>().unwrap_err(),
+            "Unknown position field `other`"
+        );
+        let text: String = position()
+            .to_string()
+            .lines()
+            .filter(|line| !line.starts_with("scores"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(text.parse::<Position>().unwrap_err(), "Missing `scores`");
+    }
+
+    #[test]
+    fn plays_session() {
+        let position = position();
+        let input = format!(
+            "nile {0}\n{1}go 7\nbogus\nquit\nnile {0}\n",
+            PROTOCOL_VERSION, position
+        );
+        let mut output = Vec::new();
+        run_bot(
+            "brute",
+            |player_count| Box::new(Brute::new(player_count)),
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        let expected_turn = Brute::new(3).take_turn(
+            &position.rack,
+            &position.board,
+            &position.rules,
+            8,
+            vec![-4, 20],
+        )[0]
+        .clone();
+        assert_eq!(
+            lines,
+            vec![
+                "ready brute".to_owned(),
+                format!("turn 7 {}", TurnRecord::Placements(expected_turn)),
+                "error Unknown command `bogus`".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut output = Vec::new();
+        run_bot(
+            "brute",
+            |player_count| Box::new(Brute::new(player_count)),
+            "nile 1\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "error Unsupported protocol `nile 1`\n"
+        );
+    }
+}
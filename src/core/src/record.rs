@@ -0,0 +1,35 @@
+use crate::layout::BoardLayout;
+use crate::log::TilePlacementEvent;
+use crate::rules::RuleSet;
+
+use serde::{Deserialize, Serialize};
+
+/// A single committed turn
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TurnRecord {
+    /// Tiles placed during the turn in the order they extend the river. Universal tiles are
+    /// recorded with the tile path the player chose for them
+    Placements(Vec<TilePlacementEvent>),
+    /// The player couldn't play and discarded their tiles
+    CantPlay,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPlayer {
+    pub name: String,
+    pub is_cpu: bool,
+}
+
+/// Everything needed to reproduce a game turn by turn. Because all randomness comes from `seed`,
+/// the setup and the turns fully determine every tile drawn
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    /// Players in seating order
+    pub players: Vec<RecordedPlayer>,
+    #[serde(default)]
+    pub layout: BoardLayout,
+    pub rules: RuleSet,
+    /// Every committed turn in order, beginning with the randomly chosen first player
+    pub turns: Vec<TurnRecord>,
+}
@@ -0,0 +1,154 @@
+use crate::ai::Brute;
+use crate::nile::Nile;
+use crate::player::PlayerSetup;
+use crate::record::GameRecord;
+
+/// Steps forwards and backwards through the turns of a `GameRecord`
+#[derive(Debug, Clone)]
+pub struct Replay {
+    record: GameRecord,
+    /// `states[i]` is the game after `i` turns
+    states: Vec<Nile>,
+    /// Index in `states` of the current state
+    position: usize,
+}
+
+impl Replay {
+    /// Replays every turn in `record` up front so that stepping is cheap and an invalid record
+    /// is detected immediately. The replay begins before the first turn
+    pub fn new(record: GameRecord) -> Result<Self, String> {
+        // CPU strategies are never consulted because every turn comes from the record
+        let player_count = record.players.len();
+        let players = record
+            .players
+            .iter()
+            .map(|p| {
+                if p.is_cpu {
+                    PlayerSetup::cpu(p.name.clone(), Box::new(Brute::new(player_count)))
+                } else {
+                    PlayerSetup::human(p.name.clone())
+                }
+            })
+            .collect();
+        let mut nile = Nile::new(
+            players,
+            record.layout.clone(),
+            record.rules.clone(),
+            Some(record.seed),
+        )?;
+        let mut states = Vec::with_capacity(record.turns.len() + 1);
+        states.push(nile.clone());
+        for (i, turn) in record.turns.iter().enumerate() {
+            nile.play_turn(turn).map_err(|e| {
+                let msg = match e {
+                    crate::error::Error::Msg(msg) => msg,
+                    crate::error::Error::Cell(cell_error) => cell_error.msg,
+                };
+                format!("Invalid turn {}: {}", i + 1, msg)
+            })?;
+            states.push(nile.clone());
+        }
+        Ok(Self {
+            record,
+            states,
+            position: 0,
+        })
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /// Game state after `position()` turns
+    pub fn nile(&self) -> &Nile {
+        &self.states[self.position]
+    }
+
+    /// Number of turns that have been replayed
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Total number of turns in the record
+    pub fn turn_count(&self) -> usize {
+        self.record.turns.len()
+    }
+
+    /// Returns whether there was a turn to step forward to
+    pub fn step_forward(&mut self) -> bool {
+        self.seek(self.position + 1)
+    }
+
+    /// Returns whether there was a turn to step back to
+    pub fn step_backward(&mut self) -> bool {
+        self.position > 0 && self.seek(self.position - 1)
+    }
+
+    /// Jump to the game state after `position` turns. Returns whether `position` is valid
+    pub fn seek(&mut self, position: usize) -> bool {
+        if position < self.states.len() {
+            self.position = position;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::BoardLayout;
+    use crate::nile::Engine;
+    use crate::player::PlayerSetup;
+    use crate::record::TurnRecord;
+    use crate::rules::RuleSet;
+
+    fn cpu_game() -> Engine {
+        // Only CPU players so the whole game is played in the constructor
+        let engine = Engine::new(
+            PlayerSetup::humans_and_cpus(Vec::new(), 2),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(5),
+        )
+        .unwrap();
+        assert!(engine.has_ended());
+        engine
+    }
+
+    #[test]
+    fn replays_to_same_result() {
+        let engine = cpu_game();
+        let mut target = Replay::new(engine.record()).unwrap();
+        assert_eq!(target.position(), 0);
+        assert!(target.seek(target.turn_count()));
+        assert!(target.nile().has_ended());
+        assert_eq!(target.nile().players(), engine.players());
+        assert_eq!(target.nile().turns(), &engine.record().turns[..]);
+        assert!(!target.step_forward());
+    }
+
+    #[test]
+    fn step_backward_restores_earlier_state() {
+        let engine = cpu_game();
+        let mut target = Replay::new(engine.record()).unwrap();
+        let initial_players = target.nile().players().clone();
+        assert!(!target.step_backward());
+        assert!(target.step_forward());
+        assert!(target.step_forward());
+        assert_eq!(target.nile().turns().len(), 2);
+        assert!(target.step_backward());
+        assert!(target.step_backward());
+        assert_eq!(target.nile().players(), &initial_players);
+        assert!(target.nile().turns().is_empty());
+    }
+
+    #[test]
+    fn invalid_turn() {
+        let mut record = cpu_game().record();
+        record.turns.insert(0, TurnRecord::Placements(Vec::new()));
+        let res = Replay::new(record);
+        assert!(matches!(res, Err(e) if e.starts_with("Invalid turn 1")));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::player::MAX_TILES;
+use crate::tile::{Tile, TileBox};
+
+use serde::{Deserialize, Serialize};
+
+use std::ops::RangeInclusive;
+
+/// Optional rules that are chosen at the beginning of a game
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub cant_play_check: CantPlayCheck,
+    pub river_check: RiverCheck,
+    /// Bonus for playing every tile in the rack in a single turn
+    pub all_tiles_bonus: i16,
+    pub all_tiles_bonus_when: AllTilesBonus,
+    pub cant_play_penalty: CantPlayPenalty,
+    pub min_players: usize,
+    pub max_players: usize,
+    /// Tiles players draw up to at the end of their turns
+    pub rack_size: usize,
+    /// Whether the game ends when the next player's rack is empty because the tile box has run
+    /// out. Otherwise players with empty racks can't play until every player can't play
+    pub empty_rack_ends_game: bool,
+    /// Whether the tiles left in each rack when the game ends are subtracted from the player's
+    /// score
+    pub rack_penalty_at_end: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            cant_play_check: CantPlayCheck::default(),
+            river_check: RiverCheck::default(),
+            all_tiles_bonus: 20,
+            all_tiles_bonus_when: AllTilesBonus::default(),
+            cant_play_penalty: CantPlayPenalty::default(),
+            min_players: 2,
+            max_players: 4,
+            rack_size: MAX_TILES,
+            empty_rack_ends_game: true,
+            rack_penalty_at_end: false,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn player_counts(&self) -> RangeInclusive<usize> {
+        self.min_players..=self.max_players
+    }
+
+    /// Bonus earned by a turn that began with `rack_len` tiles and played `played` of them
+    pub fn all_tiles_bonus(&self, rack_len: usize, played: usize) -> i16 {
+        let applies = played > 0
+            && played == rack_len
+            && match self.all_tiles_bonus_when {
+                AllTilesBonus::EmptyRack => true,
+                AllTilesBonus::FullRack => rack_len == self.rack_size,
+            };
+        if applies {
+            self.all_tiles_bonus
+        } else {
+            0
+        }
+    }
+
+    /// Penalty for not being able to play with `rack` as a positive number
+    pub fn cant_play_penalty(&self, rack: &[Tile]) -> i16 {
+        match self.cant_play_penalty {
+            CantPlayPenalty::RackValue => rack.iter().map(|t| t.score()).sum(),
+            CantPlayPenalty::Fixed(penalty) => penalty,
+        }
+    }
+
+    /// Checks the rules can be played with a complete set of tiles
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.min_players == 0 || self.min_players > self.max_players {
+            return Err(format!(
+                "Invalid player counts {}-{}",
+                self.min_players, self.max_players
+            ));
+        }
+        if self.rack_size == 0 {
+            return Err("Racks must hold at least one tile".to_owned());
+        }
+        let tile_count = TileBox::all_tiles().len();
+        if self.max_players * self.rack_size > tile_count {
+            return Err(format!(
+                "{} players can't each draw {} of the {} tiles",
+                self.max_players, self.rack_size, tile_count
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// When playing every tile in the rack earns `RuleSet::all_tiles_bonus`
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AllTilesBonus {
+    /// Whenever a turn empties the rack, including smaller racks once the tile box runs out
+    #[default]
+    EmptyRack,
+    /// Only when the rack was full at the start of the turn
+    FullRack,
+}
+
+/// What a player loses when they can't play
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CantPlayPenalty {
+    /// The total score of the tiles they discard
+    #[default]
+    RackValue,
+    /// The same number of points every time. `Fixed(0)` means no penalty
+    Fixed(i16),
+}
+
+/// How a player's claim that they can't play is verified
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CantPlayCheck {
+    /// Trust the player. Useful for casual games
+    Off,
+    /// Reject the claim if the player has a legal move
+    #[default]
+    Reject,
+    /// Reject the claim and show the player one of their legal moves
+    RejectWithMove,
+}
+
+/// How far ahead a turn is checked for leaving the river with nowhere to go. `Reachable` is the
+/// encirclement check the game has always had; `Escape` is a house rule that relaxes it
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RiverCheck {
+    /// Only reject turns after which the river can't be extended at all. Looser than the
+    /// original encirclement check: the river can be boxed in, in which case the game ends
+    /// through empty racks or every player being unable to play. Allows turns, e.g. onto a
+    /// bonus inside a closed-off region, that `Reachable` rejects
+    Escape,
+    /// Reject turns after which the river can no longer reach the end-of-game column, as the
+    /// encirclement check always has
+    #[default]
+    Reachable,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate_rules() {
+        assert!(RuleSet::default().validate().is_ok());
+        let target = RuleSet {
+            min_players: 5,
+            ..RuleSet::default()
+        };
+        assert!(target.validate().is_err());
+        let target = RuleSet {
+            rack_size: 0,
+            ..RuleSet::default()
+        };
+        assert!(target.validate().is_err());
+        let target = RuleSet {
+            max_players: 30,
+            ..RuleSet::default()
+        };
+        assert!(target.validate().is_err());
+    }
+
+    #[test]
+    fn all_tiles_bonus_when() {
+        let target = RuleSet::default();
+        assert_eq!(target.all_tiles_bonus(3, 3), 20);
+        assert_eq!(target.all_tiles_bonus(5, 4), 0);
+        assert_eq!(target.all_tiles_bonus(0, 0), 0);
+        let target = RuleSet {
+            all_tiles_bonus_when: AllTilesBonus::FullRack,
+            ..RuleSet::default()
+        };
+        assert_eq!(target.all_tiles_bonus(3, 3), 0);
+        assert_eq!(target.all_tiles_bonus(5, 5), 20);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::log::Log;
+use crate::nile::Nile;
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the format written by `Engine::save`. Must be incremented whenever a change to the
+/// saved types makes older saves unreadable
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    nile: &'a Nile,
+    log: &'a Log,
+}
+
+/// The version is checked separately by `SaveHeader`
+#[derive(Deserialize)]
+struct Save {
+    nile: Nile,
+    log: Log,
+}
+
+/// Parsed before the rest of the save so an incompatible save results in a clear error instead
+/// of a confusing parse error
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub(crate) fn save(nile: &Nile, log: &Log) -> Result<String, String> {
+    serde_json::to_string(&SaveRef {
+        version: SAVE_VERSION,
+        nile,
+        log,
+    })
+    .map_err(|e| format!("Failed to save game: {}", e))
+}
+
+pub(crate) fn load(save: &str) -> Result<(Nile, Log), String> {
+    let header: SaveHeader =
+        serde_json::from_str(save).map_err(|e| format!("Invalid save: {}", e))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "Incompatible save version {}. Only version {} is supported",
+            header.version, SAVE_VERSION
+        ));
+    }
+    let mut save: Save =
+        serde_json::from_str(save).map_err(|e| format!("Corrupted save: {}", e))?;
+    save.nile
+        .validate()
+        .map_err(|e| format!("Corrupted save: {}", e))?;
+    save.nile.restore_strategies();
+    Ok((save.nile, save.log))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ai::Difficulty;
+    use crate::layout::BoardLayout;
+    use crate::nile::Engine;
+    use crate::player::PlayerSetup;
+    use crate::rules::{CantPlayCheck, RuleSet};
+    use crate::tile::Coordinates;
+
+    fn setup() -> Engine {
+        Engine::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned(), "player2".to_owned()], 0),
+            BoardLayout::default(),
+            // Tests use “can’t play” to advance the game
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(8),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut target = setup();
+        target.cant_play().unwrap();
+        let saved = target.save().unwrap();
+        let mut loaded = Engine::load(&saved).unwrap();
+        assert_eq!(loaded.save().unwrap(), saved);
+        assert_eq!(loaded.seed(), 8);
+        assert_eq!(loaded.current_turn(), target.current_turn());
+        assert_eq!(loaded.players(), target.players());
+        // Discarded tiles are returned to the same positions in the tile box
+        target.cant_play().unwrap();
+        loaded.cant_play().unwrap();
+        assert_eq!(loaded.players(), target.players());
+        assert_eq!(loaded.save().unwrap(), target.save().unwrap());
+    }
+
+    #[test]
+    fn round_trip_undo_redo() {
+        let mut target = setup();
+        target.select_rack_tile(0).unwrap();
+        target.place_tile(Coordinates(5, 5)).unwrap();
+        target.place_tile(Coordinates(5, 6)).unwrap();
+        target.undo().unwrap();
+        let saved = target.save().unwrap();
+        let mut loaded = Engine::load(&saved).unwrap();
+        assert!(loaded.board().has_tile(Coordinates(5, 5)));
+        assert!(loaded.can_undo());
+        assert!(loaded.can_redo());
+        loaded.redo().unwrap();
+        assert!(loaded.board().has_tile(Coordinates(5, 6)));
+
+        let mut loaded = Engine::load(&saved).unwrap();
+        loaded.undo().unwrap();
+        assert!(loaded.current_turn_placements().is_empty());
+        assert_eq!(loaded.current_player().tiles().len(), 5);
+    }
+
+    #[test]
+    fn invalid_json() {
+        let res = Engine::load("{\"version\": 1, \"nile\": ");
+        assert!(matches!(res, Err(e) if e.starts_with("Invalid save")));
+    }
+
+    #[test]
+    fn incompatible_version() {
+        let mut save: serde_json::Value = serde_json::from_str(&setup().save().unwrap()).unwrap();
+        save["version"] = serde_json::Value::from(SAVE_VERSION + 1);
+        let res = Engine::load(&save.to_string());
+        assert!(matches!(res, Err(e) if e.starts_with("Incompatible save version")));
+    }
+
+    #[test]
+    fn loaded_cpu_players_play() {
+        let target = Engine::new(
+            PlayerSetup::humans_and_cpus(vec!["player1".to_owned()], 1),
+            BoardLayout::default(),
+            RuleSet {
+                cant_play_check: CantPlayCheck::Off,
+                ..RuleSet::default()
+            },
+            Some(8),
+        )
+        .unwrap();
+        let mut loaded = Engine::load(&target.save().unwrap()).unwrap();
+        let turn_count = loaded.record().turns.len();
+        loaded.cant_play().unwrap();
+        // The CPU player took its turn right after
+        assert_eq!(loaded.record().turns.len(), turn_count + 2);
+        assert!(!loaded.current_player().is_cpu());
+    }
+
+    #[test]
+    fn loaded_cpu_difficulties_play_the_same() {
+        let mut target = Nile::new(
+            PlayerSetup::humans_and_cpus_with_difficulties(
+                Vec::new(),
+                &[Difficulty::Easy, Difficulty::Medium],
+                8,
+            ),
+            BoardLayout::default(),
+            RuleSet::default(),
+            Some(8),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            target.play_cpu_turn().unwrap();
+        }
+        let (mut loaded, _) = load(&save(&target, &Log::new()).unwrap()).unwrap();
+        for _ in 0..6 {
+            target.play_cpu_turn().unwrap();
+            loaded.play_cpu_turn().unwrap();
+        }
+        assert_eq!(loaded.record().turns, target.record().turns);
+    }
+
+    #[test]
+    fn missing_tile() {
+        let mut save: serde_json::Value = serde_json::from_str(&setup().save().unwrap()).unwrap();
+        save["nile"]["tile_box"]["tiles"]
+            .as_array_mut()
+            .unwrap()
+            .pop();
+        let res = Engine::load(&save.to_string());
+        assert!(matches!(res, Err(e) if e.contains("Tiles don’t match")));
+    }
+
+    #[test]
+    fn invalid_current_turn() {
+        let mut save: serde_json::Value = serde_json::from_str(&setup().save().unwrap()).unwrap();
+        save["nile"]["current_turn"] = serde_json::Value::from(2);
+        let res = Engine::load(&save.to_string());
+        assert!(matches!(res, Err(e) if e.contains("not a valid player index")));
+    }
+}
@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
 
 /// For scoring, it's valuable to keep display penalties (negative scores)
@@ -6,7 +9,7 @@ use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
 ///
 /// Both `add` and `sub` should be >= 0, unless the `TurnScore` instance is
 /// part of an undo or revert option like removing a tile.
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TurnScore {
     pub add: i16,
     pub sub: i16,
@@ -102,6 +105,192 @@ impl Ord for TurnScore {
     }
 }
 
+/// Where the points of a turn came from, by category, as on the paper score pads. Like
+/// `TurnScore`, undoing an action adds the negation of its breakdown. Penalties are positive
+/// numbers that are subtracted
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Values of the tiles played
+    pub tiles: i16,
+    /// Bonuses of the cells tiles were played on
+    pub bonus_cells: i16,
+    /// Penalties of the cells tiles were played on, including the end-of-game column
+    pub penalty_cells: i16,
+    /// Bonus of the end-of-game column cell the river reached
+    pub end_column: i16,
+    /// Bonus for playing every tile in the rack
+    pub all_tiles: i16,
+    /// Tiles discarded because the player couldn't play
+    pub cant_play: i16,
+    /// Tiles left in the rack when the game ended
+    pub rack_remainder: i16,
+}
+
+impl ScoreBreakdown {
+    /// Placing a tile worth `tile_score` on a cell with `bonus`
+    pub(crate) fn placement(tile_score: i16, bonus: i16, is_end_column: bool) -> Self {
+        let (bonus_cells, end_column) = match (bonus > 0, is_end_column) {
+            (false, _) => (0, 0),
+            (true, false) => (bonus, 0),
+            (true, true) => (0, bonus),
+        };
+        Self {
+            tiles: tile_score,
+            bonus_cells,
+            penalty_cells: (-bonus).max(0),
+            end_column,
+            ..Self::default()
+        }
+    }
+
+    pub fn turn_score(&self) -> TurnScore {
+        TurnScore {
+            add: self.tiles + self.bonus_cells + self.end_column + self.all_tiles,
+            sub: self.penalty_cells + self.cant_play + self.rack_remainder,
+        }
+    }
+
+    pub fn score(&self) -> i16 {
+        self.turn_score().score()
+    }
+}
+
+/// The categories that earned or lost points, e.g. “tiles 28, bonus cells 40, penalty cells
+/// −20”
+impl Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let categories = [
+            ("tiles", self.tiles),
+            ("bonus cells", self.bonus_cells),
+            ("penalty cells", -self.penalty_cells),
+            ("end column", self.end_column),
+            ("all tiles", self.all_tiles),
+            ("can’t play", -self.cant_play),
+            ("rack remainder", -self.rack_remainder),
+        ];
+        let mut first = true;
+        for (name, points) in categories.iter().filter(|(_, points)| *points != 0) {
+            if !first {
+                write!(f, ", ")?;
+            }
+            first = false;
+            if *points < 0 {
+                write!(f, "{} −{}", name, -points)?;
+            } else {
+                write!(f, "{} {}", name, points)?;
+            }
+        }
+        if first {
+            write!(f, "no points")?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for ScoreBreakdown {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            tiles: self.tiles + rhs.tiles,
+            bonus_cells: self.bonus_cells + rhs.bonus_cells,
+            penalty_cells: self.penalty_cells + rhs.penalty_cells,
+            end_column: self.end_column + rhs.end_column,
+            all_tiles: self.all_tiles + rhs.all_tiles,
+            cant_play: self.cant_play + rhs.cant_play,
+            rack_remainder: self.rack_remainder + rhs.rack_remainder,
+        }
+    }
+}
+
+impl AddAssign for ScoreBreakdown {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Neg for ScoreBreakdown {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            tiles: -self.tiles,
+            bonus_cells: -self.bonus_cells,
+            penalty_cells: -self.penalty_cells,
+            end_column: -self.end_column,
+            all_tiles: -self.all_tiles,
+            cant_play: -self.cant_play,
+            rack_remainder: -self.rack_remainder,
+        }
+    }
+}
+
+impl Sum for ScoreBreakdown {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// A player's place at the end of a game
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Standing {
+    /// Index of the player in the game's players
+    pub player: usize,
+    /// 1 for the winners. Tied players share a rank and the ranks after them are skipped, e.g.
+    /// 1, 1, 3
+    pub rank: usize,
+    pub score: i16,
+}
+
+/// Every player's place at the end of a game, after final scoring
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FinalStandings {
+    /// Highest score first. Tied players are in seating order
+    standings: Vec<Standing>,
+}
+
+impl FinalStandings {
+    /// Ranks players by their total scores, given in seating order
+    pub fn new(scores: &[i16]) -> Self {
+        let mut standings: Vec<Standing> = scores
+            .iter()
+            .enumerate()
+            .map(|(player, score)| Standing {
+                player,
+                rank: 1 + scores.iter().filter(|other| *other > score).count(),
+                score: *score,
+            })
+            .collect();
+        // Stable, so tied players stay in seating order
+        standings.sort_by_key(|standing| standing.rank);
+        Self { standings }
+    }
+
+    pub fn standings(&self) -> &[Standing] {
+        &self.standings
+    }
+
+    /// Indices of the players with the highest score
+    pub fn winners(&self) -> Vec<usize> {
+        self.standings
+            .iter()
+            .take_while(|standing| standing.rank == 1)
+            .map(|standing| standing.player)
+            .collect()
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.winners().len() > 1
+    }
+
+    pub fn rank(&self, player: usize) -> Option<usize> {
+        self.standings
+            .iter()
+            .find(|standing| standing.player == player)
+            .map(|standing| standing.rank)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -162,7 +351,7 @@ mod test {
     #[test]
     fn add_assign() {
         let target = TurnScore { add: 40, sub: 8 };
-        let mut copy = target.clone();
+        let mut copy = target;
         let rhs = TurnScore { add: 16, sub: 0 };
         copy += rhs;
         assert_eq!(copy, target + rhs);
@@ -176,4 +365,73 @@ mod test {
         let right = TurnScore::from(-80);
         assert_eq!(left.max(right), left);
     }
+
+    #[test]
+    fn standings_share_tied_ranks() {
+        let target = FinalStandings::new(&[50, 80, 50, 20]);
+        let ranks: Vec<_> = target
+            .standings()
+            .iter()
+            .map(|s| (s.player, s.rank, s.score))
+            .collect();
+        assert_eq!(ranks, [(1, 1, 80), (0, 2, 50), (2, 2, 50), (3, 4, 20)]);
+        assert_eq!(target.winners(), [1]);
+        assert!(!target.is_tie());
+        assert_eq!(target.rank(2), Some(2));
+        assert_eq!(target.rank(4), None);
+    }
+
+    #[test]
+    fn tied_winners() {
+        let target = FinalStandings::new(&[-10, 30, 30]);
+        assert_eq!(target.winners(), [1, 2]);
+        assert!(target.is_tie());
+    }
+
+    #[test]
+    fn breakdown_matches_turn_score() {
+        let placements = [
+            (10, 0, false),
+            (8, 40, false),
+            (35, -20, false),
+            (5, 500, true),
+        ];
+        for (tile_score, bonus, is_end_column) in placements {
+            let target = ScoreBreakdown::placement(tile_score, bonus, is_end_column);
+            assert_eq!(
+                target.turn_score(),
+                TurnScore::from(tile_score) + TurnScore::from(bonus)
+            );
+            assert_eq!((-target).turn_score(), -target.turn_score());
+        }
+        let target: ScoreBreakdown = placements
+            .iter()
+            .map(|(t, b, e)| ScoreBreakdown::placement(*t, *b, *e))
+            .sum();
+        assert_eq!(
+            target,
+            ScoreBreakdown {
+                tiles: 58,
+                bonus_cells: 40,
+                penalty_cells: 20,
+                end_column: 500,
+                ..ScoreBreakdown::default()
+            }
+        );
+    }
+
+    #[test]
+    fn display_breakdown() {
+        let target = ScoreBreakdown {
+            tiles: 28,
+            bonus_cells: 40,
+            penalty_cells: 20,
+            ..ScoreBreakdown::default()
+        };
+        assert_eq!(
+            target.to_string(),
+            "tiles 28, bonus cells 40, penalty cells −20"
+        );
+        assert_eq!(ScoreBreakdown::default().to_string(), "no points");
+    }
 }
@@ -1,13 +1,15 @@
-use rand::rngs::ThreadRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::fmt;
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Eq))]
 pub enum Rotation {
+    #[default]
     None,
     Clockwise90,
     Clockwise180,
@@ -21,14 +23,8 @@ pub static ROTATIONS: [Rotation; 4] = [
     Rotation::Clockwise270,
 ];
 
-impl Default for Rotation {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 /// A unique location on the board
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Coordinates(pub i8, pub i8);
 
 impl fmt::Display for Coordinates {
@@ -39,7 +35,7 @@ impl fmt::Display for Coordinates {
 
 /// A game piece that can be placed on the board
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
     /// ```text
     /// ---
@@ -102,15 +98,109 @@ impl Tile {
     }
 }
 
+/// Source of all randomness in a game. A given seed always produces the same sequence, which
+/// makes games reproducible
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "GameRngState", from = "GameRngState")]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        // Fills the buffer so `get_word_pos` doesn't underflow before the first draw. The stream
+        // is unchanged
+        rng.set_word_pos(0);
+        Self { seed, rng }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(random_seed())
+    }
+}
+
+/// Seed for a game that wasn't given one
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Serialized form of `GameRng`. The generator can be restored from its seed and how far it has
+/// advanced
+#[derive(Serialize, Deserialize)]
+struct GameRngState {
+    seed: u64,
+    word_pos: u128,
+}
+
+impl From<GameRng> for GameRngState {
+    fn from(game_rng: GameRng) -> Self {
+        Self {
+            seed: game_rng.seed,
+            word_pos: game_rng.rng.get_word_pos(),
+        }
+    }
+}
+
+impl From<GameRngState> for GameRng {
+    fn from(state: GameRngState) -> Self {
+        let mut game_rng = Self::new(state.seed);
+        game_rng.rng.set_word_pos(state.word_pos);
+        game_rng
+    }
+}
+
 /// Holds tiles that can still be drawn by a player
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileBox {
     tiles: VecDeque<Tile>,
-    rng: ThreadRng,
+    /// determines the draw order and where discarded tiles are returned
+    rng: GameRng,
 }
 
 impl Default for TileBox {
     fn default() -> Self {
+        Self::with_rng(GameRng::default())
+    }
+}
+
+impl TileBox {
+    pub fn new(mut tiles: Vec<Tile>, mut rng: GameRng) -> Self {
+        tiles.shuffle(&mut rng);
+        Self {
+            tiles: VecDeque::from(tiles),
+            rng,
+        }
+    }
+
+    /// Creates a shuffled box with the full set of tiles from the original game
+    pub fn with_rng(rng: GameRng) -> Self {
+        Self::new(Self::all_tiles(), rng)
+    }
+
+    /// Every tile in the game, unshuffled
+    pub fn all_tiles() -> Vec<Tile> {
         let mut tiles = Vec::with_capacity(104);
         // Frequencies from the original game board
         Self::push_n(&mut tiles, Tile::Left135, 10);
@@ -122,19 +212,11 @@ impl Default for TileBox {
         Self::push_n(&mut tiles, Tile::Diagonal, 20);
         Self::push_n(&mut tiles, Tile::Corner90, 10);
         Self::push_n(&mut tiles, Tile::Universal, 4);
-
-        Self::new(tiles)
+        tiles
     }
-}
 
-impl TileBox {
-    pub fn new(mut tiles: Vec<Tile>) -> Self {
-        let mut rng = rand::thread_rng();
-        tiles.shuffle(&mut rng);
-        Self {
-            tiles: VecDeque::from(tiles),
-            rng,
-        }
+    pub(crate) fn tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.iter()
     }
 
     fn push_n(tiles: &mut Vec<Tile>, tile: Tile, n: usize) {
@@ -176,15 +258,19 @@ mod test {
 
     #[test]
     fn draw_empty() {
-        let mut target = TileBox::default();
-        target.tiles = VecDeque::new();
-        assert!(matches!(target.draw(), None));
+        let mut target = TileBox {
+            tiles: VecDeque::new(),
+            ..TileBox::default()
+        };
+        assert!(target.draw().is_none());
     }
 
     #[test]
     fn discard_inserts() {
-        let mut target = TileBox::default();
-        target.tiles = VecDeque::from(vec![Tile::Corner90, Tile::Straight]);
+        let mut target = TileBox {
+            tiles: VecDeque::from([Tile::Corner90, Tile::Straight]),
+            ..TileBox::default()
+        };
         let original_size = target.tiles.len();
         let discarded_tiles = vec![Tile::Universal, Tile::Left135];
         let discarded_size = discarded_tiles.len();
@@ -194,10 +280,42 @@ mod test {
         assert!(target.tiles.contains(&Tile::Left135));
     }
 
+    #[test]
+    fn same_seed_same_order() {
+        let mut target1 = TileBox::with_rng(GameRng::new(7));
+        let mut target2 = TileBox::with_rng(GameRng::new(7));
+        assert_eq!(target1.tiles, target2.tiles);
+        let discarded_tiles = vec![Tile::Universal, Tile::Left135, Tile::Diagonal];
+        target1.discard(discarded_tiles.clone());
+        target2.discard(discarded_tiles);
+        assert_eq!(target1.tiles, target2.tiles);
+    }
+
+    #[test]
+    fn restored_rng_continues_sequence() {
+        let mut target = GameRng::new(99);
+        target.next_u64();
+        let mut restored = GameRng::from(GameRngState::from(target.clone()));
+        assert_eq!(restored.next_u64(), target.next_u64());
+    }
+
+    #[test]
+    fn unused_rng_restores() {
+        let mut target = GameRng::new(99);
+        let mut restored = GameRng::from(GameRngState::from(target.clone()));
+        assert_eq!(restored.next_u64(), target.next_u64());
+        assert_eq!(
+            GameRng::new(99).next_u64(),
+            ChaCha8Rng::seed_from_u64(99).next_u64()
+        );
+    }
+
     #[test]
     fn insert_at_random_when_empty() {
-        let mut target = TileBox::default();
-        target.tiles = VecDeque::default();
+        let mut target = TileBox {
+            tiles: VecDeque::default(),
+            ..TileBox::default()
+        };
         target.insert_at_random(Tile::Corner90);
         assert_eq!(target.tiles[0], Tile::Corner90)
     }
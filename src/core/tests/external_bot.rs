@@ -0,0 +1,97 @@
+use nile::{
+    Board, BoardLayout, Brute, CPUPlayer, ExternalBot, Nile, PlayerSetup, RuleSet, TileArray,
+};
+
+use std::time::Duration;
+
+fn spawn_brute_bot() -> ExternalBot {
+    ExternalBot::spawn(env!("CARGO_BIN_EXE_brute_bot"), &[]).unwrap()
+}
+
+fn play(players: Vec<PlayerSetup>, seed: u64) -> Nile {
+    let mut nile = Nile::new(
+        players,
+        BoardLayout::default(),
+        RuleSet::default(),
+        Some(seed),
+    )
+    .unwrap();
+    while !nile.has_ended() {
+        nile.play_cpu_turn().unwrap();
+    }
+    nile
+}
+
+#[test]
+fn handshake() {
+    assert_eq!(spawn_brute_bot().name(), "brute");
+}
+
+#[test]
+fn missing_program() {
+    assert!(ExternalBot::spawn("./no-such-nile-bot", &[]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn unresponsive_program() {
+    let err =
+        ExternalBot::spawn_with_timeout("sleep", &["5"], Duration::from_millis(100)).unwrap_err();
+    assert_eq!(err, "Bot didn't reply within 100ms");
+}
+
+#[cfg(unix)]
+#[test]
+fn ignores_late_replies() {
+    // Replies to its first request too late, then to both right away
+    let script = r#"
+        read version
+        echo ready slow
+        while read line; do
+            case "$line" in
+                "go 1") sleep 0.7; echo "turn 1 Str@10,0/R0" ;;
+                go*) echo "turn ${line#go } X" ;;
+            esac
+        done
+    "#;
+    let mut bot =
+        ExternalBot::spawn_with_timeout("sh", &["-c", script], Duration::from_millis(500)).unwrap();
+    let mut take_turn = || {
+        bot.take_turn(
+            &TileArray::new(),
+            &Board::new(),
+            &RuleSet::default(),
+            0,
+            vec![0],
+        )
+    };
+    assert!(take_turn().is_empty());
+    // The late turn for the first request isn't taken as the reply to the second
+    assert!(take_turn().is_empty());
+}
+
+#[test]
+fn plays_like_brute() {
+    let external = play(
+        vec![
+            PlayerSetup::cpu("external".to_owned(), Box::new(spawn_brute_bot())),
+            PlayerSetup::cpu("brute".to_owned(), Box::new(Brute::new(2))),
+        ],
+        7,
+    );
+    let local = play(
+        vec![
+            PlayerSetup::cpu("local".to_owned(), Box::new(Brute::new(2))),
+            PlayerSetup::cpu("brute".to_owned(), Box::new(Brute::new(2))),
+        ],
+        7,
+    );
+    assert_eq!(external.turns(), local.turns());
+    let scores = |nile: &Nile| {
+        nile.players()
+            .iter()
+            .map(|p| p.total_score())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(scores(&external), scores(&local));
+}
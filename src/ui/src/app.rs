@@ -1,3 +1,4 @@
+use nile::{Difficulty, RuleSet};
 use yew::prelude::*;
 
 use crate::{
@@ -10,7 +11,8 @@ pub struct App {
     link: ComponentLink<App>,
     player_names: Vec<String>,
     has_confirmed: bool,
-    cpu_player_count: u8,
+    /// Difficulty of each CPU player
+    cpu_difficulties: Vec<Difficulty>,
     game_number: u32,
     should_show_shortcuts: bool,
     should_show_new_game_form: bool,
@@ -22,6 +24,7 @@ pub enum Msg {
     RemovePlayer,
     AddCpuPlayer,
     RemoveCpuPlayer,
+    CpuDifficultyChange(CpuDifficultyChange),
     Confirm,
     SetShouldShowShortcuts(bool),
     SetShouldShowNewGameForm(bool),
@@ -34,6 +37,11 @@ pub struct PlayerNameChange {
     pub name: String,
 }
 
+pub struct CpuDifficultyChange {
+    pub idx: usize,
+    pub difficulty: Difficulty,
+}
+
 impl Component for App {
     type Message = Msg;
     type Properties = ();
@@ -42,7 +50,7 @@ impl Component for App {
         Self {
             player_names: vec![String::default()],
             has_confirmed: false,
-            cpu_player_count: 1,
+            cpu_difficulties: vec![Difficulty::default()],
             game_number: 1,
             should_show_shortcuts: false,
             should_show_new_game_form: false,
@@ -53,8 +61,8 @@ impl Component for App {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::AddPlayer => {
-                let total_player_count = self.player_names.len() + self.cpu_player_count as usize;
-                if total_player_count < 4 {
+                let total_player_count = self.player_names.len() + self.cpu_difficulties.len();
+                if total_player_count < RuleSet::default().max_players {
                     self.player_names.push(String::default());
                     true
                 } else {
@@ -71,12 +79,19 @@ impl Component for App {
             }
             Msg::RemovePlayer => self.player_names.pop().is_some(),
             Msg::AddCpuPlayer => {
-                let cpu_player_count = (self.cpu_player_count + 1).min(3);
-                update_if_changed(&mut self.cpu_player_count, cpu_player_count)
+                if self.cpu_difficulties.len() < 3 {
+                    self.cpu_difficulties.push(Difficulty::default());
+                    true
+                } else {
+                    false
+                }
             }
-            Msg::RemoveCpuPlayer => {
-                let cpu_player_count = (self.cpu_player_count - 1).max(0);
-                update_if_changed(&mut self.cpu_player_count, cpu_player_count)
+            Msg::RemoveCpuPlayer => self.cpu_difficulties.pop().is_some(),
+            Msg::CpuDifficultyChange(CpuDifficultyChange { idx, difficulty }) => {
+                match self.cpu_difficulties.get_mut(idx) {
+                    Some(cpu_difficulty) => update_if_changed(cpu_difficulty, difficulty),
+                    None => false,
+                }
             }
             Msg::Confirm => update_if_changed(&mut self.has_confirmed, true),
             Msg::SetShouldShowShortcuts(should_show_shortcuts) => {
@@ -93,7 +108,7 @@ impl Component for App {
             }
             Msg::Reset => {
                 self.player_names = vec![String::default()];
-                self.cpu_player_count = 1;
+                self.cpu_difficulties = vec![Difficulty::default()];
                 true
             }
         }
@@ -111,14 +126,14 @@ impl Component for App {
             <div id="app-container">
                 <main>{ if self.has_confirmed { html! {
                     <InGame player_names={ self.player_names.clone() }
-                        cpu_player_count={ self.cpu_player_count }
+                        cpu_difficulties={ self.cpu_difficulties.clone() }
                         should_show_shortcuts={ self.should_show_shortcuts }
                         on_new_game={ on_new_game }
                         on_shortcuts_modal={ on_shortcuts_modal }
                     />
                 } } else { html! {
                     <Landing player_names={ self.player_names.clone() }
-                        cpu_player_count={ self.cpu_player_count }
+                        cpu_difficulties={ self.cpu_difficulties.clone() }
                         should_show_new_game_form={ self.should_show_new_game_form }
                         dispatch={ dispatch }
                     />
@@ -69,6 +69,8 @@ pub mod tile_cell {
         Locked,
         Selectable,
         Selected,
+        /// Suggested placement that isn't on the board
+        Hint,
     }
 
     #[derive(Clone, Properties)]
@@ -109,7 +111,9 @@ pub mod tile_cell {
         }
 
         fn view(&self) -> Html {
+            // Clicking a hint places the selected tile there
             let is_selectable = self.props.selection != Selection::Locked;
+            let is_draggable = is_selectable && self.props.selection != Selection::Hint;
             let on_click = {
                 let on_select = self.props.on_select.clone();
                 Callback::from(move |e: MouseEvent| {
@@ -126,13 +130,14 @@ pub mod tile_cell {
                 let on_select = self.props.on_select.clone();
                 Callback::from(move |e: DragEvent| {
                     e.prevent_default();
-                    if is_selectable {
+                    if is_draggable {
                         on_select.emit(());
                     }
                 })
             };
             let selected_css_class = match self.props.selection {
                 Selection::Selected => Some("selected"),
+                Selection::Hint => Some("hint"),
                 _ => None,
             };
             let universal_css_class = match self.props.tile_path_type {
@@ -147,7 +152,7 @@ pub mod tile_cell {
                     )
                     style={ rotation_to_css(self.props.rotation) }
                     onclick={ on_click }
-                    draggable={ is_selectable.to_string() }
+                    draggable={ is_draggable.to_string() }
                     ondrag={ on_drag }
                     ondragstart={ on_drag_start }
                 >
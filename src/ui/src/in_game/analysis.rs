@@ -0,0 +1,101 @@
+use yew::prelude::*;
+use yewdux::{component::WithDispatch, prelude::DispatchProps};
+
+use super::state::{Action, GameStore};
+use crate::components::{utils::update_if_changed, Button};
+
+/// Number of blunders listed in the panel
+const BLUNDER_COUNT: usize = 3;
+
+/// Post-game report of the points each player missed, available once the end-of-game modal has
+/// been dismissed. The report is only computed when asked for, because it searches every turn of
+/// the game
+pub struct AnalysisImpl {
+    props: DispatchProps<GameStore>,
+}
+pub type Analysis = WithDispatch<AnalysisImpl>;
+
+impl Component for AnalysisImpl {
+    type Properties = DispatchProps<GameStore>;
+    type Message = ();
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        update_if_changed(&mut self.props, props)
+    }
+
+    fn view(&self) -> Html {
+        let state = self.props.state();
+        if !state.nile.has_ended() || state.modal.is_some() {
+            return html! {};
+        }
+        let analysis = match &state.analysis {
+            Some(analysis) => analysis,
+            None => {
+                let on_analyze = self.props.callback(|_| Action::Analyze);
+                return html! {
+                    <div class="analysis">
+                        <Button class=classes!("nile-blue-bg")
+                            on_click={ on_analyze }
+                            title="Analyze game"
+                            aria_label="Compare every turn to the best one available"
+                        >
+                            { "Analyze game" }
+                        </Button>
+                    </div>
+                };
+            }
+        };
+        let players = state.nile.players();
+        html! {
+            <div class="analysis">
+                <h2>{ "Game analysis" }</h2>
+                <table class="scores">
+                    <thead>
+                        <tr>
+                            <th>{ "Player" }</th>
+                            <th>{ "Accuracy" }</th>
+                            <th>{ "Missed" }</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for analysis.players().iter().enumerate().map(|(i, player_analysis)| html! {
+                            <tr key={ i }>
+                                <td>{ players[i].name() }</td>
+                                <td>{ format!("{:.0}%", player_analysis.accuracy * 100.0) }</td>
+                                <td>{ player_analysis.missed }</td>
+                            </tr>
+                        }) }
+                    </tbody>
+                </table>
+                <h3>{ "Biggest blunders" }</h3>
+                { if analysis.blunders(BLUNDER_COUNT).is_empty() {
+                    html! { <p>{ "Every turn was the best available" }</p> }
+                } else {
+                    html! {
+                        <ol>
+                            { for analysis.blunders(BLUNDER_COUNT).into_iter().map(|turn| html! {
+                                <li key={ turn.turn } title={ turn.best.to_string() }>
+                                    { format!(
+                                        "Turn {}: {} scored {} but could have scored {}",
+                                        turn.turn + 1,
+                                        players[turn.player].name(),
+                                        turn.score,
+                                        turn.best_score,
+                                    ) }
+                                </li>
+                            }) }
+                        </ol>
+                    }
+                } }
+            </div>
+        }
+    }
+}
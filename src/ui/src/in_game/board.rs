@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use nile::{Cell, Coordinates, BOARD_DIM};
+use nile::{Cell, Coordinates, TilePlacementEvent, BOARD_DIM};
 use yew::prelude::*;
 use yewdux::prelude::Dispatcher;
 use yewdux::{component::WithDispatch, prelude::DispatchProps};
@@ -33,6 +33,7 @@ impl Component for BoardImpl {
             || old_state.nile.current_turn_placements() != new_state.nile.current_turn_placements()
             || old_state.nile.selected_board_tile() != new_state.nile.selected_board_tile()
             || old_state.nile.error_cells() != new_state.nile.error_cells()
+            || old_state.hint != new_state.hint
         {
             self.props = props;
             true
@@ -46,6 +47,7 @@ impl Component for BoardImpl {
         let board = state.nile.board();
         let current_turn_placements = state.nile.current_turn_placements();
         let selection = state.nile.selected_board_tile();
+        let hint_placements = state.hint.as_ref().map(|hint| &hint.placements);
 
         let cells = (0..BOARD_DIM as i8)
             .map(|i| {
@@ -63,10 +65,16 @@ impl Component for BoardImpl {
                                 let is_error = state.nile.error_cells().map_or(false, |error_cells| error_cells.contains(&coordinates));
                                 let on_select = self.props.callback(move |_| Action::SelectBoardTile(coordinates));
                                 let on_drop = self.props.callback(move |_| Action::PlaceTile(coordinates));
+                                let tile_cell_type = TileCellType::from((cell, board.is_end_game_cell(coordinates)));
+                                let hint_placement = hint_placements
+                                    .and_then(|placements| placements.iter().find(|p| p.coordinates == coordinates));
 
                                 html! {
                                     <td key={ j }>
-                                        { Self::view_cell(cell, TileCellType::from((cell, board.is_end_game_cell(coordinates))), Selection::from((is_seleted, current_turn_placements.contains(&coordinates))), is_error, on_select, on_drop) }
+                                        { match hint_placement {
+                                            Some(placement) => Self::view_hint_cell(placement, tile_cell_type, on_drop),
+                                            None => Self::view_cell(cell, tile_cell_type, Selection::from((is_seleted, current_turn_placements.contains(&coordinates))), is_error, on_select, on_drop),
+                                        } }
                                     </td>
                                 }
                             }) }
@@ -146,4 +154,20 @@ impl BoardImpl {
             }
         }
     }
+
+    fn view_hint_cell(
+        placement: &TilePlacementEvent,
+        tile_cell_type: TileCellType,
+        on_drop: Callback<()>,
+    ) -> Html {
+        html! {
+            <TileCell tile_path_type={ placement.tile_path_type }
+                rotation={ placement.rotation }
+                tile_cell_type={ tile_cell_type }
+                selection={ Selection::Hint }
+                is_error={ false }
+                on_select={ on_drop }
+            />
+        }
+    }
 }
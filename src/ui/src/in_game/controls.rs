@@ -70,6 +70,7 @@ impl Component for ControlsImpl {
         let on_redo = self.props.callback(|_| Action::Redo);
         let on_end_turn = self.props.callback(|_| Action::EndTurn);
         let on_cant_play = self.props.callback(|_| Action::CantPlay);
+        let on_hint = self.props.callback(|_| Action::Hint);
         let on_click_dropdown = {
             let is_tile_path_selector_open = self.is_tile_path_selector_open;
             self.link
@@ -146,6 +147,15 @@ impl Component for ControlsImpl {
                     <CarbonIcon name="close" size={ Size::S24 } />
                     { "Can’t play" }
                 </Button>
+                <Button is_enabled={ !state.nile.has_ended() }
+                    class=classes!("nile-blue-bg")
+                    on_click={ on_hint }
+                    title="Hint"
+                    aria_label="Show the best turn for the current player"
+                >
+                    <CarbonIcon name="idea" size={ Size::S24 } />
+                    { "Hint" }
+                </Button>
             </div>
         }
     }
@@ -1,4 +1,5 @@
 use super::{
+    analysis::Analysis,
     board::Board,
     controls::Controls,
     player::Players,
@@ -7,6 +8,7 @@ use super::{
 use crate::components::utils::update_if_changed;
 use crate::components::ErrorModal;
 
+use nile::Difficulty;
 use yew::{
     prelude::*,
     services::{keyboard::KeyListenerHandle, KeyboardService},
@@ -28,7 +30,7 @@ pub struct Props {
     #[prop_or_default]
     pub dispatch: DispatchProps<GameStore>,
     pub player_names: Vec<String>,
-    pub cpu_player_count: u8,
+    pub cpu_difficulties: Vec<Difficulty>,
 }
 
 impl DispatchPropsMut for Props {
@@ -47,7 +49,7 @@ impl Component for GameImpl {
         let dispatch = &props.dispatch;
         dispatch.send(Action::NewGame(NewGameOptions {
             player_names: props.player_names.clone(),
-            cpu_player_count: props.cpu_player_count,
+            cpu_difficulties: props.cpu_difficulties.clone(),
         }));
         let handle = {
             let rotate_selected = dispatch.callback(Action::RotateSelectedTile);
@@ -56,6 +58,7 @@ impl Component for GameImpl {
             let redo = dispatch.callback(|_| Action::Redo);
             let end_turn = dispatch.callback(|_| Action::EndTurn);
             let cant_play = dispatch.callback(|_| Action::CantPlay);
+            let hint = dispatch.callback(|_| Action::Hint);
             let select_rack_tile =
                 dispatch.callback(|rack_idx| Action::SelectRackTile(SelectRackTile { rack_idx }));
             let dismiss = dispatch.callback(|_| Action::Dismiss);
@@ -76,6 +79,7 @@ impl Component for GameImpl {
                         "r" => redo.emit(()),
                         "E" => end_turn.emit(()),
                         "C" => cant_play.emit(()),
+                        "h" => hint.emit(()),
                         "1" | "2" | "3" | "4" | "5" => {
                             if let Some(num) = keyboard_event
                                 .key()
@@ -118,6 +122,7 @@ impl Component for GameImpl {
                 </section>
                 <section>
                     <Players />
+                    <Analysis />
                 </section>
             </>
         }
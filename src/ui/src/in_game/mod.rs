@@ -1,3 +1,4 @@
+mod analysis;
 mod board;
 mod controls;
 mod game;
@@ -5,6 +6,7 @@ mod header;
 mod player;
 mod state;
 
+use nile::Difficulty;
 use yew::prelude::*;
 
 use crate::components::{utils::update_if_changed, Button, Container, Modal};
@@ -18,7 +20,7 @@ pub struct InGame {
 #[derive(Clone, Properties)]
 pub struct Props {
     pub player_names: Vec<String>,
-    pub cpu_player_count: u8,
+    pub cpu_difficulties: Vec<Difficulty>,
     pub should_show_shortcuts: bool,
     pub on_new_game: Callback<()>,
     pub on_shortcuts_modal: Callback<bool>,
@@ -28,7 +30,7 @@ impl PartialEq for Props {
     fn eq(&self, other: &Self) -> bool {
         // exclude `Callback`s
         self.player_names == other.player_names
-            && self.cpu_player_count == other.cpu_player_count
+            && self.cpu_difficulties == other.cpu_difficulties
             && self.should_show_shortcuts == other.should_show_shortcuts
     }
 }
@@ -69,7 +71,7 @@ impl Component for InGame {
                 </Button>
                 { self.view_shortcuts_help_modal() }
                 <Game player_names={ self.props.player_names.clone() }
-                    cpu_player_count={ self.props.cpu_player_count }
+                    cpu_difficulties={ self.props.cpu_difficulties.clone() }
                 />
             </Container>
         }
@@ -78,7 +80,7 @@ impl Component for InGame {
 
 impl InGame {
     fn view_shortcuts_help_modal(&self) -> Html {
-        const SHORTCUT_BINDINGS: [(&str, &str); 8] = [
+        const SHORTCUT_BINDINGS: [(&str, &str); 9] = [
             ("q", "rotate counter-clockwise"),
             ("e", "rotate clockwise"),
             ("x", "remove tile"),
@@ -86,6 +88,7 @@ impl InGame {
             ("r", "redo"),
             ("E", "end turn"),
             ("C", "can’t play"),
+            ("h", "hint"),
             ("ESC", "dismiss modal"),
         ];
 
@@ -165,6 +165,12 @@ mod player {
             let player = &state.nile.players()[self.props.id as usize];
             let current_turn_score_fwd = Self::sum_turn_scores(player.scores());
             let current_turn_score = player.current_turn_score();
+            // Only display where the current turn's points came from during the turn
+            let current_turn_title = if is_current_turn {
+                player.current_turn_breakdown().to_string()
+            } else {
+                String::new()
+            };
             let mut score_fwd = 0;
             let selected_tile_idx = state.nile.selected_rack_tile();
             let on_select = self
@@ -193,9 +199,9 @@ mod player {
                             </tr>
                         </thead>
                         <tbody>
-                            { if_render_html(self.props.are_scores_expanded, player.scores().iter().enumerate().map(|(i, score)| {
+                            { if_render_html(self.props.are_scores_expanded, player.scores().iter().zip(player.score_breakdowns()).enumerate().map(|(i, (score, breakdown))| {
                                 let row_html = html! {
-                                    <tr key={ i }>
+                                    <tr key={ i } title={ breakdown.to_string() }>
                                         <td>{ score_fwd }</td>
                                         <td>{ score.add }</td>
                                         <td>{ score.sub }</td>
@@ -205,7 +211,7 @@ mod player {
                                 score_fwd = score_fwd + score.add - score.sub;
                                 row_html
                             }).collect::<Html>()) }
-                            <tr key={ player.scores().len() }>
+                            <tr key={ player.scores().len() } title={ current_turn_title }>
                                 <td>
                                     { current_turn_score_fwd }
                                 </td>
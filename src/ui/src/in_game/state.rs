@@ -1,4 +1,7 @@
-use nile::{console, Coordinates, Engine, Player, SelectedTile, TilePath, TilePathType};
+use nile::{
+    console, random_seed, BoardLayout, Coordinates, Difficulty, Engine, GameAnalysis, Hint,
+    PlayerSetup, RuleSet, SelectedTile, TilePath, TilePathType,
+};
 use yewdux::prelude::{Reducer, ReducerStore};
 
 use crate::components::utils::update_if_changed;
@@ -9,6 +12,10 @@ pub struct State {
     pub nile: Engine,
     /// Modal state for displaying errors and end-of-game message
     pub modal: Option<Modal>,
+    /// Suggested turn displayed on the board until the next action
+    pub hint: Option<Hint>,
+    /// Missed points of every turn, computed on request once the game has ended
+    pub analysis: Option<GameAnalysis>,
 }
 
 #[derive(Debug)]
@@ -25,6 +32,9 @@ pub enum Action {
     Redo,
     EndTurn,
     CantPlay,
+    Hint,
+    /// Analyze the ended game
+    Analyze,
     Dismiss,
 }
 
@@ -37,7 +47,7 @@ pub enum Modal {
 #[derive(Debug, Clone, PartialEq)]
 pub struct NewGameOptions {
     pub player_names: Vec<String>,
-    pub cpu_player_count: u8,
+    pub cpu_difficulties: Vec<Difficulty>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -55,10 +65,23 @@ pub struct SelectRackTile {
 pub type GameStore = ReducerStore<State>;
 
 impl State {
-    pub fn new_game(player_names: Vec<String>, cpu_player_count: u8) -> Self {
+    pub fn new_game(player_names: Vec<String>, cpu_difficulties: &[Difficulty]) -> Self {
+        let seed = random_seed();
         Self {
-            nile: Engine::new(player_names, cpu_player_count).unwrap(),
+            nile: Engine::new(
+                PlayerSetup::humans_and_cpus_with_difficulties(
+                    player_names,
+                    cpu_difficulties,
+                    seed,
+                ),
+                BoardLayout::default(),
+                RuleSet::default(),
+                Some(seed),
+            )
+            .unwrap(),
             modal: None,
+            hint: None,
+            analysis: None,
         }
     }
 
@@ -89,17 +112,35 @@ impl Reducer for State {
     type Action = Action;
 
     fn new() -> Self {
-        Self::new_game(vec![String::default()], 1)
+        Self::new_game(vec![String::default()], &[Difficulty::default()])
     }
 
     fn reduce(&mut self, action: Self::Action) -> yewdux::prelude::Changed {
         // console::info(&format!("Received action: {:?}", action));
-        match action {
+        // Actions that change the board or turn make the hint stale
+        let keeps_hint = matches!(
+            action,
+            Action::SelectRackTile(_) | Action::SelectBoardTile(_) | Action::Hint | Action::Dismiss
+        );
+        let has_cleared_hint = !keeps_hint && self.hint.take().is_some();
+        let has_changed = match action {
             Action::NewGame(NewGameOptions {
                 player_names,
-                cpu_player_count,
+                cpu_difficulties,
             }) => {
-                self.nile = Engine::new(player_names, cpu_player_count).expect("nile engine");
+                let seed = random_seed();
+                self.nile = Engine::new(
+                    PlayerSetup::humans_and_cpus_with_difficulties(
+                        player_names,
+                        &cpu_difficulties,
+                        seed,
+                    ),
+                    BoardLayout::default(),
+                    RuleSet::default(),
+                    Some(seed),
+                )
+                .expect("nile engine");
+                self.analysis = None;
                 true
             }
             Action::SelectRackTile(select_rack_tile) => self
@@ -140,8 +181,11 @@ impl Reducer for State {
                 .unwrap_or_else(|e| self.set_error(e)),
             Action::EndTurn => self.end_turn(),
             Action::CantPlay => self.cant_play(),
+            Action::Hint => self.hint(),
+            Action::Analyze => self.analyze(),
             Action::Dismiss => self.dismiss(),
-        }
+        };
+        has_changed || has_cleared_hint
     }
 }
 
@@ -190,33 +234,47 @@ impl State {
             .unwrap_or_else(|e| self.set_error(e))
     }
 
+    fn hint(&mut self) -> yewdux::prelude::Changed {
+        if self.nile.has_ended() {
+            return false;
+        }
+        match self.nile.hint().into_iter().next() {
+            Some(hint) => update_if_changed(&mut self.hint, Some(hint)),
+            None => self.set_error(
+                "There are no legal moves with these tiles. Use “can’t play”".to_owned(),
+            ),
+        }
+    }
+
     fn set_end_of_game(&mut self) -> yewdux::prelude::Changed {
-        let winning_score = self
-            .nile
-            .players()
-            .iter()
-            .fold(i16::MIN, |acc, p| i16::max(acc, p.total_score()));
-        let winners: Vec<&Player> = self
-            .nile
-            .players()
-            .iter()
-            .filter(|p| p.total_score() == winning_score)
+        let standings = match self.nile.final_standings() {
+            Some(standings) => standings,
+            None => return false,
+        };
+        let players = self.nile.players();
+        let winners: Vec<&str> = standings
+            .winners()
+            .into_iter()
+            .map(|idx| players[idx].name())
             .collect();
-        let msg = match winners.len() {
-            0 => unreachable!(),
-            1 => format!("{} has won", winners[0].name()),
-            _ => format!(
-                "{} tied",
-                winners
-                    .iter()
-                    .map(|p| p.name())
-                    .collect::<Vec<&str>>()
-                    .join(", ")
-            ),
+        let msg = if standings.is_tie() {
+            format!("{} tied", winners.join(", "))
+        } else {
+            format!("{} has won", winners[0])
         };
         update_if_changed(&mut self.modal, Some(Modal::EndOfGame(msg)))
     }
 
+    fn analyze(&mut self) -> yewdux::prelude::Changed {
+        if !self.nile.has_ended() || self.analysis.is_some() {
+            return false;
+        }
+        match GameAnalysis::new(&self.nile.record()) {
+            Ok(analysis) => update_if_changed(&mut self.analysis, Some(analysis)),
+            Err(e) => self.set_error(e),
+        }
+    }
+
     fn set_error(&mut self, msg: String) -> yewdux::prelude::Changed {
         console::error(&msg);
         update_if_changed(&mut self.modal, Some(Modal::Error(msg)))
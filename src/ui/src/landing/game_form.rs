@@ -1,7 +1,8 @@
+use nile::{Difficulty, RuleSet, DIFFICULTIES};
 use yew::prelude::*;
 
 use crate::{
-    app::PlayerNameChange,
+    app::{CpuDifficultyChange, PlayerNameChange},
     components::{
         carbon_icon::{CarbonIcon, Size},
         utils::update_if_changed,
@@ -16,7 +17,7 @@ pub struct GameForm {
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {
     pub player_names: Vec<String>,
-    pub cpu_player_count: u8,
+    pub cpu_difficulties: Vec<Difficulty>,
     pub dispatch: Callback<crate::app::Msg>,
 }
 
@@ -37,9 +38,11 @@ impl Component for GameForm {
     }
 
     fn view(&self) -> Html {
-        let total_player_count =
-            self.props.player_names.len() + self.props.cpu_player_count as usize;
-        let can_start = (2..=4).contains(&total_player_count);
+        let cpu_player_count = self.props.cpu_difficulties.len();
+        let total_player_count = self.props.player_names.len() + cpu_player_count;
+        let player_counts = RuleSet::default().player_counts();
+        let can_start = player_counts.contains(&total_player_count);
+        let can_add = total_player_count < *player_counts.end();
         let on_add_player = self
             .props
             .dispatch
@@ -71,7 +74,7 @@ impl Component for GameForm {
                 <Button title="Add player"
                     aria_label="Add player"
                     class=classes!("nile-blue-bg")
-                    is_enabled={ total_player_count < 4 }
+                    is_enabled={ can_add }
                     on_click={ on_add_player }
                 >
                     <CarbonIcon name="add_filled" size={ Size::S16 } />
@@ -85,11 +88,11 @@ impl Component for GameForm {
                     <CarbonIcon name="subtract" size={ Size::S16 } />
                 </Button>
                 <br />
-                <span class="cpu-count">{ format!("CPU players: {}", self.props.cpu_player_count) }</span>
+                <span class="cpu-count">{ format!("CPU players: {}", cpu_player_count) }</span>
                 <Button title="Add CPU player"
                     aria_label="Add CPU player"
                     class=classes!("nile-blue-bg")
-                    is_enabled={ total_player_count < 4 }
+                    is_enabled={ can_add }
                     on_click={ on_add_cpu_player }
                 >
                     <CarbonIcon name="add_filled" size={ Size::S16 } />
@@ -97,12 +100,18 @@ impl Component for GameForm {
                 <Button title="Remove CPU player"
                     aria_label="Remove CPU player"
                     class=classes!("nile-blue-bg")
-                    is_enabled={ total_player_count > 1 && self.props.cpu_player_count > 0 }
+                    is_enabled={ total_player_count > 1 && cpu_player_count > 0 }
                     on_click={ on_rm_cpu_player }
                 >
                     <CarbonIcon name="subtract" size={ Size::S16 } />
                 </Button>
                 <br />
+                { for { self.props.cpu_difficulties
+                    .iter()
+                    .enumerate()
+                    .map(|(i, difficulty)| self.view_cpu_difficulty_select(i, *difficulty))
+                } }
+                { if cpu_player_count > 0 { html! { <br /> } } else { html! {} } }
                 <Button title={ if can_start { "Start new game" } else { "Need at least two players" } }
                     class=classes!("river-turquoise-bg")
                     aria_label="Start new game"
@@ -151,4 +160,36 @@ impl GameForm {
             </>
         }
     }
+
+    fn view_cpu_difficulty_select(&self, i: usize, difficulty: Difficulty) -> Html {
+        let id = format!("cpu{}", i);
+        let on_change = {
+            let dispatch = self.props.dispatch.clone();
+            Callback::from(move |e: ChangeData| {
+                if let ChangeData::Select(select) = e {
+                    if let Some(difficulty) = usize::try_from(select.selected_index())
+                        .ok()
+                        .and_then(|idx| DIFFICULTIES.get(idx))
+                    {
+                        dispatch.emit(crate::app::Msg::CpuDifficultyChange(CpuDifficultyChange {
+                            idx: i,
+                            difficulty: *difficulty,
+                        }))
+                    }
+                }
+            })
+        };
+        html! {
+            <>
+                <label for={ id.clone() }>
+                    { format!("CPU {} difficulty", i + 1) }
+                </label>
+                <select id={ id } onchange={ on_change }>
+                    { for DIFFICULTIES.iter().map(|d| html! {
+                        <option selected={ *d == difficulty }>{ d.to_string() }</option>
+                    }) }
+                </select>
+            </>
+        }
+    }
 }
@@ -3,7 +3,7 @@ mod cover_art;
 mod game_form;
 mod header;
 
-use nile::Tile;
+use nile::{Difficulty, Tile};
 use yew::prelude::*;
 
 use crate::{
@@ -21,7 +21,7 @@ pub struct Landing {
 #[derive(Clone, Properties, PartialEq)]
 pub struct Props {
     pub player_names: Vec<String>,
-    pub cpu_player_count: u8,
+    pub cpu_difficulties: Vec<Difficulty>,
     pub should_show_new_game_form: bool,
     pub dispatch: Callback<app::Msg>,
 }
@@ -65,7 +65,7 @@ impl Component for Landing {
                     <section>
                         <h3 class="section-title">{ "new game" }</h3>
                         <GameForm player_names={ self.props.player_names.clone() }
-                            cpu_player_count={ self.props.cpu_player_count }
+                            cpu_difficulties={ self.props.cpu_difficulties.clone() }
                             dispatch={ self.props.dispatch.clone() }
                         />
                     </section>